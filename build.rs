@@ -1,4 +1,8 @@
-use std::process::Command;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    process::Command,
+};
 fn main() {
     let tailwind_cmd = "pnpm dlx tailwindcss -i ./styles/tailwind.css -o ./assets/main.css";
     if cfg!(target_os = "windows") {
@@ -7,6 +11,13 @@ fn main() {
         Command::new("sh").arg("-c").arg(tailwind_cmd).status()
     }
     .expect("error running tailwind");
+
+    // Fingerprint of the stylesheet for cache busting, see `mode::ASSETS_VERSION`.
+    let mut hasher = DefaultHasher::new();
+    std::fs::read("assets/main.css")
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    println!("cargo:rustc-env=ASSETS_VERSION={:016x}", hasher.finish());
     println!("cargo:rerun-if-changed=tailwind.config.js");
     println!("cargo:rerun-if-changed=input.css");
 }
@@ -0,0 +1,311 @@
+use crate::{components::sync::last_processed_layer, networks, AppState};
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{Path, Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use metrics::counter;
+use sea_orm::{DatabaseConnection, DbErr};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+const DEFAULT_MAX_BYTES: usize = 32 << 20;
+const DEFAULT_RECENT_SECS: u64 = 10;
+/// Finalized layers never change, so browsers may keep them for a year.
+const FINAL_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+struct Entry {
+    body: Bytes,
+    content_type: Option<HeaderValue>,
+    etag: HeaderValue,
+    cache_control: HeaderValue,
+    /// `None` for finalized layers, which are kept until evicted.
+    expires: Option<Instant>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Entries {
+    by_key: HashMap<String, Entry>,
+    /// Keys by last use, oldest first, for eviction.
+    by_use: BTreeMap<u64, String>,
+    bytes: usize,
+    clock: u64,
+}
+
+impl Entries {
+    fn get(&mut self, key: &str, now: Instant) -> Option<&Entry> {
+        let expired = self
+            .by_key
+            .get(key)?
+            .expires
+            .is_some_and(|expires| expires <= now);
+        if expired {
+            self.remove(key);
+            return None;
+        }
+
+        self.clock += 1;
+        let entry = self.by_key.get_mut(key)?;
+        self.by_use.remove(&entry.last_used);
+        self.by_use.insert(self.clock, key.to_string());
+        entry.last_used = self.clock;
+        Some(entry)
+    }
+
+    fn insert(&mut self, key: String, mut entry: Entry, max_bytes: usize) {
+        self.remove(&key);
+        if entry.body.len() > max_bytes {
+            return;
+        }
+
+        while self.bytes + entry.body.len() > max_bytes {
+            match self.by_use.first_key_value() {
+                Some((_, oldest)) => {
+                    let oldest = oldest.clone();
+                    self.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+
+        self.clock += 1;
+        entry.last_used = self.clock;
+        self.bytes += entry.body.len();
+        self.by_use.insert(self.clock, key.clone());
+        self.by_key.insert(key, entry);
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.by_key.remove(key) {
+            self.by_use.remove(&entry.last_used);
+            self.bytes -= entry.body.len();
+        }
+    }
+}
+
+/// In-memory cache of rendered layer responses, bounded by the total size
+/// of the bodies it holds and evicting the least recently used first.
+pub struct ResponseCache {
+    entries: Mutex<Entries>,
+    max_bytes: usize,
+    recent_ttl: Duration,
+}
+
+impl ResponseCache {
+    pub fn new(max_bytes: usize, recent_ttl: Duration) -> Self {
+        ResponseCache {
+            entries: Mutex::new(Entries::default()),
+            max_bytes,
+            recent_ttl,
+        }
+    }
+
+    /// Sized by `CACHE_MAX_BYTES`, keeping layers that are not final yet for
+    /// `CACHE_RECENT_SECS`.
+    pub fn from_env() -> Self {
+        let max_bytes = std::env::var("CACHE_MAX_BYTES")
+            .ok()
+            .and_then(|bytes| bytes.parse().ok())
+            .unwrap_or(DEFAULT_MAX_BYTES);
+        let recent_secs = std::env::var("CACHE_RECENT_SECS")
+            .ok()
+            .and_then(|secs| secs.parse().ok())
+            .unwrap_or(DEFAULT_RECENT_SECS);
+
+        ResponseCache::new(max_bytes, Duration::from_secs(recent_secs))
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, Entries> {
+        // A panic while holding the lock cannot leave the map inconsistent
+        // in a way that matters for a cache.
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Middleware serving layer tabs from [`ResponseCache`]. Layers up to the
+/// last processed one are final and cached until evicted; later ones only
+/// for a few seconds. Responses carry an `ETag` so browsers revalidate with
+/// `If-None-Match` and get a `304`.
+///
+/// The `ETag` is weak since compression changes the bytes sent, and when
+/// several networks are served the host may pick the network, so responses
+/// vary by it too.
+pub async fn cache_layers(
+    State(state): State<AppState>,
+    Path(params): Path<HashMap<String, String>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(id) = params.get("id").and_then(|id| id.parse::<i32>().ok()) else {
+        return next.run(req).await;
+    };
+    if req.method() != Method::GET {
+        return next.run(req).await;
+    }
+
+    // Links in the body carry the network prefix.
+    let key = format!("{}{}", networks::prefix(), req.uri());
+    let vary = vary(&state);
+    let if_none_match = req.headers().get(header::IF_NONE_MATCH).cloned();
+
+    if let Some(mut response) = cached(&state.cache, &key, if_none_match.as_ref()) {
+        response.headers_mut().insert(header::VARY, vary);
+        counter!("smscan_cache_hits_total").increment(1);
+        return response;
+    }
+    counter!("smscan_cache_misses_total").increment(1);
+
+    let response = next.run(req).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+
+    let final_layer = match is_final(&state.database, id).await {
+        Ok(final_layer) => final_layer,
+        Err(err) => {
+            eprintln!("Failed to check if layer {} is final: {}", id, err);
+            return response;
+        }
+    };
+
+    let (parts, body) = response.into_parts();
+    // Pages are bounded by pagination; oversized bodies are just not kept.
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(err) => {
+            eprintln!("Failed to cache {}: {}", key, err);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let (cache_control, expires) = if final_layer {
+        (HeaderValue::from_static(FINAL_CACHE_CONTROL), None)
+    } else {
+        let ttl = state.cache.recent_ttl;
+        let cache_control = HeaderValue::from_str(&format!("public, max-age={}", ttl.as_secs()))
+            .expect("valid header value");
+        (cache_control, Some(Instant::now() + ttl))
+    };
+    let etag = etag(&body);
+    let content_type = parts.headers.get(header::CONTENT_TYPE).cloned();
+
+    let mut response = if if_none_match.as_ref() == Some(&etag) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        Response::from_parts(parts, Body::from(body.clone()))
+    };
+    set_cache_headers(response.headers_mut(), &etag, &cache_control);
+    response.headers_mut().insert(header::VARY, vary);
+
+    let entry = Entry {
+        content_type,
+        body,
+        etag,
+        cache_control,
+        expires,
+        last_used: 0,
+    };
+    state
+        .cache
+        .entries()
+        .insert(key, entry, state.cache.max_bytes);
+
+    response
+}
+
+fn cached(
+    cache: &ResponseCache,
+    key: &str,
+    if_none_match: Option<&HeaderValue>,
+) -> Option<Response> {
+    let mut entries = cache.entries();
+    let entry = entries.get(key, Instant::now())?;
+
+    let mut response = if if_none_match == Some(&entry.etag) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        let mut response = Response::new(Body::from(entry.body.clone()));
+        if let Some(content_type) = &entry.content_type {
+            response
+                .headers_mut()
+                .insert(header::CONTENT_TYPE, content_type.clone());
+        }
+        response
+    };
+    set_cache_headers(response.headers_mut(), &entry.etag, &entry.cache_control);
+
+    Some(response)
+}
+
+fn set_cache_headers(headers: &mut HeaderMap, etag: &HeaderValue, cache_control: &HeaderValue) {
+    headers.insert(header::ETAG, etag.clone());
+    headers.insert(header::CACHE_CONTROL, cache_control.clone());
+}
+
+fn etag(body: &[u8]) -> HeaderValue {
+    let hash = blake3::hash(body).to_hex();
+    HeaderValue::from_str(&format!("W/\"{}\"", &hash[..32])).expect("hex is a valid header value")
+}
+
+fn vary(state: &AppState) -> HeaderValue {
+    HeaderValue::from_static(if state.networks.len() > 1 {
+        "Accept-Encoding, Host"
+    } else {
+        "Accept-Encoding"
+    })
+}
+
+/// A layer is final once the node has processed it or any later layer.
+async fn is_final(conn: &DatabaseConnection, id: i32) -> Result<bool, DbErr> {
+    Ok(last_processed_layer(conn)
+        .await?
+        .is_some_and(|processed| id <= processed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(len: usize) -> Entry {
+        Entry {
+            body: Bytes::from(vec![0; len]),
+            content_type: None,
+            etag: HeaderValue::from_static("\"0\""),
+            cache_control: HeaderValue::from_static(FINAL_CACHE_CONTROL),
+            expires: None,
+            last_used: 0,
+        }
+    }
+
+    #[test]
+    fn entries_evict_least_recently_used() {
+        let now = Instant::now();
+        let mut entries = Entries::default();
+        entries.insert("a".into(), entry(4), 10);
+        entries.insert("b".into(), entry(4), 10);
+        assert!(entries.get("a", now).is_some());
+
+        entries.insert("c".into(), entry(4), 10);
+        assert!(entries.get("b", now).is_none());
+        assert!(entries.get("a", now).is_some());
+        assert_eq!(entries.bytes, 8);
+
+        entries.insert("big".into(), entry(11), 10);
+        assert!(entries.get("big", now).is_none());
+        assert_eq!(entries.bytes, 8);
+
+        let mut recent = entry(1);
+        recent.expires = Some(now);
+        entries.insert("recent".into(), recent, 10);
+        assert!(entries.get("recent", now).is_none());
+        assert_eq!(entries.by_key.len(), entries.by_use.len());
+    }
+}
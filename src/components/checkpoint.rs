@@ -0,0 +1,32 @@
+use crate::{db_entities::recovery, error::AppError, types::LayerId, AppState, HtmlTemplate};
+use askama::Template;
+use axum::{extract::State, response::IntoResponse};
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QuerySelect};
+
+/// Layer the node restored from a checkpoint at, if it did. Layers before it
+/// were never synced, so their history is missing from `state.sql`.
+pub async fn restore_layer(conn: &DatabaseConnection) -> Result<Option<LayerId>, DbErr> {
+    Ok(recovery::Entity::find()
+        .select_only()
+        .column_as(recovery::Column::Restore.max(), "restore")
+        .into_tuple::<Option<i32>>()
+        .one(conn)
+        .await?
+        .flatten()
+        .map(LayerId))
+}
+
+#[derive(Template)]
+#[template(path = "components/checkpoint.html")]
+struct CheckpointTemplate {
+    restore_layer: Option<LayerId>,
+}
+
+pub async fn checkpoint_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let template = CheckpointTemplate {
+        restore_layer: restore_layer(&state.database).await?,
+    };
+    Ok(HtmlTemplate(template))
+}
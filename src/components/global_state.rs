@@ -1,10 +1,22 @@
-use crate::{AppState, GlobalState, HtmlTemplate};
+use crate::{
+    components::checkpoint::restore_layer, error::AppError, networks::Network, types::LayerId,
+    AppState, GlobalState, HtmlTemplate,
+};
 use askama::Template;
 use axum::{extract::State, response::IntoResponse};
 use base64::{engine::general_purpose, Engine};
 use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use metrics::{counter, histogram};
 use serde::{Deserialize, Serialize};
-use tokio::process::Command;
+use std::time::Instant;
+use tokio::{
+    process::Command,
+    time::{interval, MissedTickBehavior},
+};
+
+/// Shorter than the minute after which the cached state is stale, so it
+/// is refreshed before readiness or the pages see it expire.
+const DEFAULT_REFRESH: std::time::Duration = std::time::Duration::from_secs(30);
 
 #[derive(Template, Debug)]
 #[template(path = "components/home/global_state.html")]
@@ -17,6 +29,7 @@ struct GlobalStateTemplate {
     current_epoch: u64,
     epoch_num_layers: u64,
     layer_duration: String,
+    restore_layer: Option<LayerId>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -83,149 +96,169 @@ struct Coinbase {
     address: String,
 }
 
-pub async fn global_state_handler(State(state): State<AppState>) -> impl IntoResponse {
+/// Returns the cached node state, refreshing it first when it is older than a
+/// minute.
+pub async fn current_global_state(state: &AppState) -> GlobalState {
     let now = Utc::now().naive_utc();
 
     let mut global_state_write = state.global_state.write().await;
     let time_since_last_update = now - global_state_write.last_state_fetch;
 
     if time_since_last_update >= Duration::seconds(60) {
-        if let Ok(new_global_state) = fetch_global_state().await {
-            *global_state_write = new_global_state;
-        } else {
-            eprintln!("Failed to fetch global state");
+        let start = Instant::now();
+        match fetch_global_state(&state.network).await {
+            Ok(new_global_state) => *global_state_write = new_global_state,
+            Err(err) => {
+                counter!("smscan_global_state_fetch_errors_total").increment(1);
+                eprintln!("Failed to fetch global state: {}", err)
+            }
         }
+        histogram!("smscan_global_state_fetch_duration_seconds")
+            .record(start.elapsed().as_secs_f64());
     }
 
-    drop(global_state_write);
-
-    let template = {
-        let global_state_read = state.global_state.read().await;
-        GlobalStateTemplate {
-            highest_atx: global_state_read.highest_atx.to_string(),
-            previous_atx: global_state_read.previous_atx.to_string(),
-            genesis_timestamp: global_state_read.genesis_timestamp.to_string(),
-            genesis_time: global_state_read.genesis_time.to_string(),
-            current_layer: global_state_read.current_layer,
-            current_epoch: global_state_read.current_epoch,
-            epoch_num_layers: global_state_read.epoch_num_layers,
-            layer_duration: global_state_read.layer_duration.to_string(),
+    global_state_write.clone()
+}
+
+/// Keeps the node state fresh from a background task, every
+/// `GLOBAL_STATE_REFRESH_SECS`, so readiness and the pages reading the cached
+/// copy do not depend on someone opening the home page.
+pub fn spawn_global_state_refresh(state: AppState) {
+    let period = std::env::var("GLOBAL_STATE_REFRESH_SECS")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .map(std::time::Duration::from_secs)
+        .unwrap_or(DEFAULT_REFRESH);
+
+    refresh_global_state_every(state, period);
+}
+
+pub fn refresh_global_state_every(state: AppState, period: std::time::Duration) {
+    tokio::spawn(async move {
+        let mut ticker = interval(period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            current_global_state(&state).await;
         }
-    };
+    });
+}
 
-    // let template = {
-    //     let new_state = fetch_global_state().await.unwrap();
-    //     GlobalStateTemplate {
-    //         highest_atx: new_state.highest_atx.to_string(),
-    //         previous_atx: new_state.previous_atx.to_string(),
-    //         genesis_timestamp: new_state.genesis_timestamp.to_string(),
-    //         genesis_time: new_state.genesis_time.to_string(),
-    //         current_layer: new_state.current_layer,
-    //         current_epoch: new_state.current_epoch,
-    //         epoch_num_layers: new_state.epoch_num_layers,
-    //         layer_duration: new_state.layer_duration.to_string(),
-    //     }
-    // };
+pub async fn global_state_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let global_state = current_global_state(&state).await;
+    let restore_layer = restore_layer(&state.database).await?;
+
+    let template = GlobalStateTemplate {
+        highest_atx: global_state.highest_atx,
+        previous_atx: global_state.previous_atx,
+        genesis_timestamp: global_state.genesis_timestamp,
+        genesis_time: global_state.genesis_time,
+        current_layer: global_state.current_layer,
+        current_epoch: global_state.current_epoch,
+        epoch_num_layers: global_state.epoch_num_layers,
+        layer_duration: global_state.layer_duration,
+        restore_layer,
+    };
 
-    HtmlTemplate(template)
+    Ok(HtmlTemplate(template))
 }
 
-async fn fetch_global_state() -> anyhow::Result<GlobalState> {
-    let node_host = std::env::var("NODE_HOST").unwrap();
+async fn fetch_global_state(network: &Network) -> anyhow::Result<GlobalState> {
+    let node_host = network
+        .node_host
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("no node host configured"))?;
 
-    let grpcurl_highest: Vec<u8> = Command::new("grpcurl")
+    let grpcurl_highest: Vec<u8> = Command::new(&network.grpcurl)
         .args([
             "-plaintext",
-            node_host.as_str(),
+            node_host,
             "spacemesh.v1.ActivationService.Highest",
         ])
         .output()
-        .await
-        .unwrap()
+        .await?
         .stdout;
 
     let highest_result: ActivationServiceHighestResult =
-        serde_json::from_str(String::from_utf8(grpcurl_highest).unwrap().as_str()).unwrap();
+        serde_json::from_str(String::from_utf8(grpcurl_highest)?.as_str())?;
 
-    let grpcurl_genesistime: Vec<u8> = Command::new("grpcurl")
+    let grpcurl_genesistime: Vec<u8> = Command::new(&network.grpcurl)
         .args([
             "-plaintext",
-            node_host.as_str(),
+            node_host,
             "spacemesh.v1.MeshService.GenesisTime",
         ])
         .output()
-        .await
-        .unwrap()
+        .await?
         .stdout;
 
     let genesis_time_result: MeshServiceGenesisTimeResult =
-        serde_json::from_str(String::from_utf8(grpcurl_genesistime).unwrap().as_str()).unwrap();
+        serde_json::from_str(String::from_utf8(grpcurl_genesistime)?.as_str())?;
 
-    let grpcurl_currentlayer: Vec<u8> = Command::new("grpcurl")
+    let grpcurl_currentlayer: Vec<u8> = Command::new(&network.grpcurl)
         .args([
             "-plaintext",
-            node_host.as_str(),
+            node_host,
             "spacemesh.v1.MeshService.CurrentLayer",
         ])
         .output()
-        .await
-        .unwrap()
+        .await?
         .stdout;
 
     let current_layer_result: MeshServiceCurrentLayerResult =
-        serde_json::from_str(String::from_utf8(grpcurl_currentlayer).unwrap().as_str()).unwrap();
+        serde_json::from_str(String::from_utf8(grpcurl_currentlayer)?.as_str())?;
 
-    let grpcurl_currentepoch: Vec<u8> = Command::new("grpcurl")
+    let grpcurl_currentepoch: Vec<u8> = Command::new(&network.grpcurl)
         .args([
             "-plaintext",
-            node_host.as_str(),
+            node_host,
             "spacemesh.v1.MeshService.CurrentEpoch",
         ])
         .output()
-        .await
-        .unwrap()
+        .await?
         .stdout;
 
     let current_epoch_result: MeshServiceCurrentEpochResult =
-        serde_json::from_str(String::from_utf8(grpcurl_currentepoch).unwrap().as_str()).unwrap();
+        serde_json::from_str(String::from_utf8(grpcurl_currentepoch)?.as_str())?;
 
-    let grpcurl_epochnumlayers: Vec<u8> = Command::new("grpcurl")
+    let grpcurl_epochnumlayers: Vec<u8> = Command::new(&network.grpcurl)
         .args([
             "-plaintext",
-            node_host.as_str(),
+            node_host,
             "spacemesh.v1.MeshService.EpochNumLayers",
         ])
         .output()
-        .await
-        .unwrap()
+        .await?
         .stdout;
 
     let epoch_num_layers_result: MeshServiceEpochNumLayersResult =
-        serde_json::from_str(String::from_utf8(grpcurl_epochnumlayers).unwrap().as_str()).unwrap();
+        serde_json::from_str(String::from_utf8(grpcurl_epochnumlayers)?.as_str())?;
 
-    let grpcurl_layerduration: Vec<u8> = Command::new("grpcurl")
+    let grpcurl_layerduration: Vec<u8> = Command::new(&network.grpcurl)
         .args([
             "-plaintext",
-            node_host.as_str(),
+            node_host,
             "spacemesh.v1.MeshService.LayerDuration",
         ])
         .output()
-        .await
-        .unwrap()
+        .await?
         .stdout;
 
     let layer_duration_result: MeshServiceLayerDurationResult =
-        serde_json::from_str(String::from_utf8(grpcurl_layerduration).unwrap().as_str()).unwrap();
+        serde_json::from_str(String::from_utf8(grpcurl_layerduration)?.as_str())?;
+
+    let genesis_timestamp = genesis_time_result.unixtime.value.parse::<i64>()?;
 
     Ok(GlobalState {
-        highest_atx: base64_to_hex(highest_result.atx.id.id),
-        previous_atx: base64_to_hex(highest_result.atx.prevAtx.id),
+        highest_atx: base64_to_hex(highest_result.atx.id.id)?,
+        previous_atx: base64_to_hex(highest_result.atx.prevAtx.id)?,
         genesis_timestamp: genesis_time_result.unixtime.value.clone(),
         genesis_time: DateTime::<Utc>::from_naive_utc_and_offset(
-            NaiveDateTime::from_timestamp_millis(
-                genesis_time_result.unixtime.value.parse::<i64>().unwrap() * 1000,
-            )
-            .unwrap(),
+            NaiveDateTime::from_timestamp_millis(genesis_timestamp * 1000)
+                .ok_or_else(|| anyhow::anyhow!("invalid genesis time {}", genesis_timestamp))?,
             Utc,
         )
         .format("%Y-%m-%d %H:%M:%S")
@@ -238,15 +271,13 @@ async fn fetch_global_state() -> anyhow::Result<GlobalState> {
     })
 }
 
-fn base64_to_hex(base64: String) -> String {
+fn base64_to_hex(base64: String) -> anyhow::Result<String> {
     let mut buffer = Vec::<u8>::new();
-    general_purpose::STANDARD
-        .decode_vec(base64, &mut buffer)
-        .unwrap();
+    general_purpose::STANDARD.decode_vec(base64, &mut buffer)?;
 
-    return buffer
+    Ok(buffer
         .iter()
         .map(|b| format!("{:02x}", b).to_string())
         .collect::<Vec<String>>()
-        .join("");
+        .join(""))
 }
@@ -1 +1,4 @@
+pub mod checkpoint;
 pub mod global_state;
+pub mod networks;
+pub mod sync;
@@ -0,0 +1,18 @@
+use crate::{AppState, HtmlTemplate};
+use askama::Template;
+use axum::{extract::State, response::IntoResponse};
+
+#[derive(Template)]
+#[template(path = "components/networks.html")]
+struct NetworksTemplate {
+    current: String,
+    networks: Vec<String>,
+}
+
+/// Network switcher of the header, empty when only one network is served.
+pub async fn networks_handler(State(state): State<AppState>) -> impl IntoResponse {
+    HtmlTemplate(NetworksTemplate {
+        current: state.network.name.clone(),
+        networks: state.networks.as_ref().clone(),
+    })
+}
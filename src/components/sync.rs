@@ -0,0 +1,52 @@
+use crate::{db_entities::layers, AppState, HtmlTemplate};
+use askama::Template;
+use axum::{extract::State, response::IntoResponse};
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder};
+
+const DEFAULT_SYNC_TOLERANCE: u64 = 10;
+
+/// Last layer the node has applied a block for, if any.
+pub async fn last_processed_layer(conn: &DatabaseConnection) -> Result<Option<i32>, DbErr> {
+    Ok(layers::Entity::find()
+        .filter(layers::Column::Processed.eq(1))
+        .order_by_desc(layers::Column::Id)
+        .one(conn)
+        .await?
+        .map(|layer| layer.id))
+}
+
+enum Degraded {
+    Syncing { processed: i32, current: u64 },
+    Unavailable,
+}
+
+#[derive(Template)]
+#[template(path = "components/sync.html")]
+struct SyncTemplate {
+    degraded: Option<Degraded>,
+}
+
+/// Banner shown on every page while the node database is unreachable or the
+/// node is more than `SYNC_TOLERANCE_LAYERS` layers behind the network.
+pub async fn sync_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let tolerance = std::env::var("SYNC_TOLERANCE_LAYERS")
+        .ok()
+        .and_then(|layers| layers.parse().ok())
+        .unwrap_or(DEFAULT_SYNC_TOLERANCE);
+    // Only the cached copy, as for the readiness probe.
+    let current = state.global_state.read().await.current_layer;
+
+    let degraded = match last_processed_layer(&state.database).await {
+        Err(err) => {
+            eprintln!("Failed to read the sync status: {}", err);
+            Some(Degraded::Unavailable)
+        }
+        Ok(processed) => {
+            let processed = processed.unwrap_or(0);
+            (current > 0 && current.saturating_sub(processed as u64) > tolerance)
+                .then_some(Degraded::Syncing { processed, current })
+        }
+    };
+
+    HtmlTemplate(SyncTemplate { degraded })
+}
@@ -15,14 +15,10 @@ pub struct Model {
     pub effective_num_units: i32,
     #[sea_orm(column_type = "Binary(BlobSize::Blob(None))", nullable)]
     pub commitment_atx: Option<Vec<u8>>,
-    #[sea_orm(column_type = "Binary(BlobSize::Blob(None))", nullable)]
-    pub nonce: Option<Vec<u8>>,
-    #[sea_orm(column_type = "Binary(BlobSize::Blob(None))", nullable)]
-    pub base_tick_height: Option<Vec<u8>>,
-    #[sea_orm(column_type = "Binary(BlobSize::Blob(None))", nullable)]
-    pub tick_count: Option<Vec<u8>>,
-    #[sea_orm(column_type = "Binary(BlobSize::Blob(None))", nullable)]
-    pub sequence: Option<Vec<u8>>,
+    pub nonce: Option<i64>,
+    pub base_tick_height: Option<i64>,
+    pub tick_count: Option<i64>,
+    pub sequence: Option<i64>,
     #[sea_orm(column_type = "Binary(BlobSize::Blob(None))", nullable)]
     pub pubkey: Option<Vec<u8>>,
     #[sea_orm(column_type = "Binary(BlobSize::Blob(None))", nullable)]
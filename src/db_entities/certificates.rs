@@ -7,12 +7,15 @@ use sea_orm::entity::prelude::*;
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub layer: i32,
-    #[sea_orm(primary_key, auto_increment = false)]
-    pub block: String,
+    #[sea_orm(
+        primary_key,
+        auto_increment = false,
+        column_type = "Binary(BlobSize::Blob(None))"
+    )]
+    pub block: Vec<u8>,
     #[sea_orm(column_type = "Binary(BlobSize::Blob(None))", nullable)]
     pub cert: Option<Vec<u8>>,
-    #[sea_orm(column_type = "Binary(BlobSize::Blob(None))")]
-    pub valid: Vec<u8>,
+    pub valid: i16,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
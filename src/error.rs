@@ -0,0 +1,81 @@
+use crate::HtmlTemplate;
+use askama::Template;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sea_orm::DbErr;
+use serde_json::json;
+
+/// Error returned by handlers, rendered as a small HTML fragment so htmx can
+/// swap it into the tab that requested it.
+#[derive(Debug)]
+pub enum AppError {
+    NotFound(String),
+    BadRequest(String),
+    TooManyRequests(String),
+    Timeout(String),
+    Database(DbErr),
+}
+
+impl From<DbErr> for AppError {
+    fn from(err: DbErr) -> Self {
+        AppError::Database(err)
+    }
+}
+
+impl From<hex::FromHexError> for AppError {
+    fn from(err: hex::FromHexError) -> Self {
+        AppError::BadRequest(format!("Invalid id: {}", err))
+    }
+}
+
+#[derive(Template)]
+#[template(path = "components/error.html")]
+struct ErrorTemplate {
+    message: String,
+}
+
+impl AppError {
+    fn status_and_message(self) -> (StatusCode, String) {
+        match self {
+            AppError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            AppError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            AppError::TooManyRequests(message) => (StatusCode::TOO_MANY_REQUESTS, message),
+            AppError::Timeout(message) => (StatusCode::SERVICE_UNAVAILABLE, message),
+            AppError::Database(err) => {
+                eprintln!("Database error: {}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to load data, please try again later.".to_string(),
+                )
+            }
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, message) = self.status_and_message();
+        (status, HtmlTemplate(ErrorTemplate { message })).into_response()
+    }
+}
+
+/// Error returned by `/api` handlers: the same statuses as [`AppError`] with
+/// an `{"error": message}` body.
+#[derive(Debug)]
+pub struct ApiError(AppError);
+
+impl<E: Into<AppError>> From<E> for ApiError {
+    fn from(err: E) -> Self {
+        ApiError(err.into())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = self.0.status_and_message();
+        (status, Json(json!({ "error": message }))).into_response()
+    }
+}
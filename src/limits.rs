@@ -0,0 +1,244 @@
+use crate::error::{ApiError, AppError};
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use metrics::counter;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::time::timeout;
+
+/// Above this many tracked clients, buckets that refilled are forgotten.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// The limiter of every route group.
+#[derive(Clone)]
+pub struct Limiters {
+    pub pages: Arc<Limiter>,
+    pub api: Arc<Limiter>,
+    pub search: Arc<Limiter>,
+}
+
+impl Limiters {
+    pub fn from_env() -> Self {
+        Limiters {
+            pages: Arc::new(Limiter::from_env(RouteGroup::Pages)),
+            api: Arc::new(Limiter::from_env(RouteGroup::Api)),
+            search: Arc::new(Limiter::from_env(RouteGroup::Search)),
+        }
+    }
+}
+
+/// Routes sharing a rate limit and timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteGroup {
+    Pages,
+    Api,
+    Search,
+}
+
+impl RouteGroup {
+    fn name(self) -> &'static str {
+        match self {
+            RouteGroup::Pages => "pages",
+            RouteGroup::Api => "api",
+            RouteGroup::Search => "search",
+        }
+    }
+
+    /// Requests per minute and seconds allowed per request by default.
+    fn defaults(self) -> (u32, u64) {
+        match self {
+            RouteGroup::Pages => (600, 10),
+            RouteGroup::Api => (120, 10),
+            RouteGroup::Search => (30, 5),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket per client IP: `per_minute` requests in a burst, refilled
+/// at the same rate. A `per_minute` of zero disables the limit.
+pub struct Limiter {
+    group: RouteGroup,
+    per_minute: u32,
+    timeout: Duration,
+    /// Take the client from `X-Forwarded-For`, for deployments behind a
+    /// reverse proxy.
+    trust_forwarded_for: bool,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+impl Limiter {
+    pub fn new(group: RouteGroup, per_minute: u32, timeout: Duration) -> Self {
+        Limiter {
+            group,
+            per_minute,
+            timeout,
+            trust_forwarded_for: false,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn trust_forwarded_for(mut self, trust: bool) -> Self {
+        self.trust_forwarded_for = trust;
+        self
+    }
+
+    /// Reads `<GROUP>_RATE_LIMIT` (requests per minute),
+    /// `<GROUP>_TIMEOUT_SECS` and `TRUST_FORWARDED_FOR`.
+    pub fn from_env(group: RouteGroup) -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok()?.parse().ok()
+        }
+
+        let prefix = group.name().to_uppercase();
+        let (per_minute, timeout_secs) = group.defaults();
+        Limiter::new(
+            group,
+            var(&format!("{}_RATE_LIMIT", prefix)).unwrap_or(per_minute),
+            Duration::from_secs(var(&format!("{}_TIMEOUT_SECS", prefix)).unwrap_or(timeout_secs)),
+        )
+        .trust_forwarded_for(var("TRUST_FORWARDED_FOR").unwrap_or(false))
+    }
+
+    /// Takes a token for `client`, or returns how long until one is back.
+    fn acquire(&self, client: IpAddr, now: Instant) -> Result<(), Duration> {
+        if self.per_minute == 0 {
+            return Ok(());
+        }
+
+        let capacity = self.per_minute as f64;
+        let per_second = capacity / 60.0;
+        let mut buckets = self
+            .buckets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if buckets.len() >= MAX_TRACKED_CLIENTS {
+            buckets.retain(|_, bucket| {
+                let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+                bucket.tokens + elapsed * per_second < capacity
+            });
+        }
+
+        let bucket = buckets.entry(client).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_second).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+        }
+    }
+
+    fn client(&self, req: &Request) -> Option<IpAddr> {
+        let forwarded = self
+            .trust_forwarded_for
+            .then(|| forwarded_for(req.headers()))
+            .flatten();
+
+        forwarded.or_else(|| {
+            req.extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|info| info.0.ip())
+        })
+    }
+
+    fn error(&self, err: AppError) -> Response {
+        match self.group {
+            RouteGroup::Api => ApiError::from(err).into_response(),
+            _ => err.into_response(),
+        }
+    }
+}
+
+/// The client closest to the proxy: the last address it appended.
+fn forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
+    headers
+        .get("x-forwarded-for")?
+        .to_str()
+        .ok()?
+        .rsplit(',')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Middleware enforcing a [`Limiter`]: `429` with `Retry-After` once the
+/// client runs out of requests, `503` when the handler takes too long.
+/// Errors are HTML fragments, or JSON for the API group.
+pub async fn enforce_limits(
+    State(limiter): State<Arc<Limiter>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let group = limiter.group.name();
+
+    if let Some(client) = limiter.client(&req) {
+        if let Err(wait) = limiter.acquire(client, Instant::now()) {
+            counter!("smscan_rate_limited_total", "group" => group).increment(1);
+            let retry_after = (wait.as_secs_f64().ceil() as u64).max(1);
+            let mut response = limiter.error(AppError::TooManyRequests(format!(
+                "Too many requests, please retry in {}s.",
+                retry_after
+            )));
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, retry_after.into());
+            return response;
+        }
+    }
+
+    match timeout(limiter.timeout, next.run(req)).await {
+        Ok(response) => response,
+        Err(_) => {
+            counter!("smscan_request_timeouts_total", "group" => group).increment(1);
+            limiter.error(AppError::Timeout(
+                "The request took too long, please try again later.".to_string(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_refill_over_time() {
+        let limiter = Limiter::new(RouteGroup::Search, 2, Duration::from_secs(1));
+        let client = IpAddr::from([10, 0, 0, 1]);
+        let other = IpAddr::from([10, 0, 0, 2]);
+        let now = Instant::now();
+
+        assert!(limiter.acquire(client, now).is_ok());
+        assert!(limiter.acquire(client, now).is_ok());
+        assert_eq!(limiter.acquire(client, now), Err(Duration::from_secs(30)));
+        assert!(limiter.acquire(other, now).is_ok());
+        assert!(limiter
+            .acquire(client, now + Duration::from_secs(30))
+            .is_ok());
+
+        let unlimited = Limiter::new(RouteGroup::Api, 0, Duration::from_secs(1));
+        assert!((0..100).all(|_| unlimited.acquire(client, now).is_ok()));
+    }
+}
@@ -1,11 +1,34 @@
 use crate::{
-    db_entities::accounts,
-    pages::{account::account_route, home::home_routes, layer::layer_routes},
+    cache::ResponseCache,
+    components::global_state,
+    db_entities::{accounts, transactions},
+    error::AppError,
+    limits::{Limiter, Limiters},
+    networks::{load_networks, networks_router, Network},
+    pages::{
+        account::account_route,
+        analytics::{analytics_api_routes, analytics_routes},
+        atx::atx_routes,
+        beacons::{beacons_api_routes, beacons_routes},
+        epoch::epoch_routes,
+        health::health_routes,
+        home::home_routes,
+        layer::{layer_routes, layer_tab_routes},
+        layers::layers_routes,
+        malfeasance::malfeasance_routes,
+        mempool::{mempool_api_routes, mempool_routes},
+        poets::poets_routes,
+        smesher::smesher_routes,
+        stats::{stats_api_routes, stats_routes, ActivityCache},
+        transaction::transaction_routes,
+    },
+    types::{Address, TxId},
 };
 use askama::Template;
 use axum::{
     extract::{Query, State},
     http::{HeaderMap, StatusCode},
+    middleware,
     response::{Html, IntoResponse, Response},
     routing::get,
     Router,
@@ -13,24 +36,46 @@ use axum::{
 use chrono::NaiveDateTime;
 use db_entities::layers;
 use dotenv::dotenv;
-use sea_orm::{ColumnTrait, Database, DatabaseConnection, EntityTrait, QueryFilter};
+use metrics_exporter_prometheus::PrometheusHandle;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
 use serde::Deserialize;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tower_http::services::ServeDir;
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+mod cache;
 pub mod components;
 mod db_entities;
+mod error;
+mod limits;
+mod metrics;
+mod mode;
+mod networks;
 mod pages;
+mod pagination;
+mod probes;
+#[cfg(test)]
+mod tests;
+mod types;
+mod view_models;
+mod vm;
+
+const DEFAULT_PORT: u16 = 3000;
 
 #[derive(Clone)]
 pub struct AppState {
     database: DatabaseConnection,
     global_state: Arc<RwLock<GlobalState>>,
+    metrics: PrometheusHandle,
+    cache: Arc<ResponseCache>,
+    activity: Arc<ActivityCache>,
+    limiters: Limiters,
+    network: Arc<Network>,
+    /// Names of every network this deployment serves.
+    networks: Arc<Vec<String>>,
 }
 #[derive(Clone, Debug)]
-struct GlobalState {
+pub struct GlobalState {
     highest_atx: String,
     previous_atx: String,
     genesis_timestamp: String,
@@ -42,20 +87,10 @@ struct GlobalState {
     last_state_fetch: NaiveDateTime,
 }
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    dotenv().ok();
-
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
-    info!("initializing router and assets");
-
-    let conn = Database::connect("sqlite://node-data/state.sql?mode=ro").await?;
-    let state = AppState {
-        database: conn,
-        global_state: Arc::new(RwLock::new(GlobalState {
+impl GlobalState {
+    /// Placeholder until the node is first queried.
+    fn loading() -> Self {
+        GlobalState {
             highest_atx: "Loading...".into(),
             previous_atx: "Loading...".into(),
             genesis_timestamp: "Loading...".into(),
@@ -65,75 +100,239 @@ async fn main() -> anyhow::Result<()> {
             epoch_num_layers: 0,
             layer_duration: "Loading...".into(),
             last_state_fetch: NaiveDateTime::from_timestamp_millis(0).unwrap(),
-        })),
+        }
+    }
+}
+
+impl AppState {
+    fn new(
+        database: DatabaseConnection,
+        metrics: PrometheusHandle,
+        network: Network,
+        networks: Vec<String>,
+    ) -> Self {
+        AppState {
+            database,
+            global_state: Arc::new(RwLock::new(GlobalState::loading())),
+            metrics,
+            cache: Arc::new(ResponseCache::from_env()),
+            activity: Arc::new(ActivityCache::default()),
+            limiters: Limiters::from_env(),
+            network: Arc::new(network),
+            networks: Arc::new(networks),
+        }
+    }
+}
+
+fn app(state: AppState) -> Router {
+    let limit = |limiter: &Arc<Limiter>| {
+        middleware::from_fn_with_state(limiter.clone(), limits::enforce_limits)
     };
 
-    let assets_path = std::env::current_dir()?;
+    let api = Router::new()
+        .nest("/api/mempool", mempool_api_routes())
+        .nest("/api/beacons", beacons_api_routes())
+        .nest("/api/analytics", analytics_api_routes())
+        .nest("/api/stats", stats_api_routes())
+        .route_layer(limit(&state.limiters.api));
 
-    let app = Router::new()
-        .layer(tower_livereload::LiveReloadLayer::new())
+    Router::new()
         .nest("/", home_routes())
-        .nest("/layer", layer_routes())
+        .nest(
+            "/layer",
+            layer_routes().merge(
+                layer_tab_routes().route_layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    cache::cache_layers,
+                )),
+            ),
+        )
+        .nest("/layers", layers_routes())
         .nest("/account", account_route())
-        .route("/search", get(search_handler))
-        .nest_service(
-            "/assets",
-            ServeDir::new(format!("{}/assets", assets_path.to_str().unwrap())),
+        .nest("/tx", transaction_routes())
+        .nest("/epoch", epoch_routes())
+        .nest("/atx", atx_routes())
+        .nest("/beacons", beacons_routes())
+        .nest("/poets", poets_routes())
+        .nest("/smesher", smesher_routes())
+        .nest("/malfeasance", malfeasance_routes())
+        .nest("/analytics", analytics_routes())
+        .nest("/stats", stats_routes())
+        .nest("/health", health_routes())
+        .nest("/mempool", mempool_routes())
+        .route_layer(limit(&state.limiters.pages))
+        .merge(api)
+        .route(
+            "/search",
+            get(search_handler).route_layer(limit(&state.limiters.search)),
         )
-        .with_state(state);
+        .route("/metrics", get(metrics::metrics_handler))
+        .route("/healthz", get(probes::healthz_handler))
+        .route("/readyz", get(probes::readyz_handler))
+        .route_layer(middleware::from_fn(metrics::track_requests))
+        .nest_service("/assets", mode::assets())
+        .with_state(state)
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv().ok();
+
+    tracing_subscriber::registry()
+        .with(mode::tracing_filter())
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    info!("initializing router and assets");
 
-    // run it, make sure you handle parsing your environment variables properly!
-    let port = std::env::var("PORT").unwrap().parse::<u16>().unwrap();
+    let metrics = metrics::install_recorder()?;
+
+    let networks = load_networks()?;
+    let names: Vec<String> = networks
+        .iter()
+        .map(|network| network.name.clone())
+        .collect();
+
+    let mut apps = vec![];
+    for network in networks {
+        // The URL may hold credentials, so only its scheme is logged.
+        let backend = network.database_url.split(':').next().unwrap_or_default();
+        info!("connecting to the {} database of {}", backend, network.name);
+        let mut conn = network.connect().await?;
+        metrics::track_database(&mut conn);
+
+        let name = network.name.clone();
+        let state = AppState::new(conn, metrics.clone(), network, names.clone());
+        global_state::spawn_global_state_refresh(state.clone());
+        metrics::spawn_chain_gauges(state.clone());
+        apps.push((name, app(state)));
+    }
+    let app = mode::layers(networks_router(apps));
+
+    let port = match std::env::var("PORT") {
+        Ok(port) => port
+            .parse::<u16>()
+            .map_err(|err| anyhow::anyhow!("invalid PORT {:?}: {}", port, err))?,
+        Err(_) => DEFAULT_PORT,
+    };
     let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
 
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+
     info!("router initialized, now listening on port {}", port);
 
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await?;
 
-    axum::serve(listener, app).await.unwrap();
+    info!("shut down");
 
     Ok(())
 }
 
+/// Resolves on Ctrl-C or SIGTERM, after which in-flight requests finish
+/// before the server stops.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            eprintln!("Failed to listen for Ctrl-C: {}", err);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                eprintln!("Failed to listen for SIGTERM: {}", err);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    info!("shutdown signal received, finishing in-flight requests");
+}
+
 #[derive(Deserialize)]
 struct Search {
     input: String,
 }
 
 #[axum::debug_handler]
-async fn search_handler(query: Query<Search>, State(state): State<AppState>) -> impl IntoResponse {
+async fn search_handler(
+    query: Query<Search>,
+    State(state): State<AppState>,
+) -> Result<HeaderMap, AppError> {
     let mut headers = HeaderMap::new();
 
     let conn = &state.database;
+    let input = query.input.trim();
+    let prefix = networks::prefix();
 
-    if let Ok(input) = query.input.parse::<i32>() {
-        let layer = layers::Entity::find_by_id(input).one(conn).await.unwrap();
+    if let Ok(id) = input.parse::<i32>() {
+        let layer = layers::Entity::find_by_id(id).one(conn).await?;
 
         if layer.is_some() {
             headers.insert(
                 "HX-Redirect",
-                format!("/layer/{}", query.input).parse().unwrap(),
+                format!("{}/layer/{}", prefix, id).parse().unwrap(),
             );
-            return headers;
+            return Ok(headers);
         }
     }
 
-    let account = accounts::Entity::find()
-        .filter(accounts::Column::Address.eq(hex::decode(query.input.clone()).unwrap()))
-        .one(conn)
-        .await
-        .unwrap();
-
-    if account.is_some() {
-        headers.insert(
-            "HX-Redirect",
-            format!("/account/{}", query.input).parse().unwrap(),
-        );
-        return headers;
+    if let Ok(id) = input.parse::<TxId>() {
+        let tx = transactions::Entity::find()
+            .filter(transactions::Column::Id.eq(id))
+            .one(conn)
+            .await?;
+
+        if tx.is_some() {
+            headers.insert(
+                "HX-Redirect",
+                format!("{}/tx/{}", prefix, input.to_lowercase())
+                    .parse()
+                    .unwrap(),
+            );
+            return Ok(headers);
+        }
+    }
+
+    let address = input
+        .parse::<Address>()
+        .ok()
+        .or_else(|| Address::from_bech32(input, &state.network.hrp));
+    if let Some(address) = address {
+        let account = accounts::Entity::find()
+            .filter(accounts::Column::Address.eq(address.clone()))
+            .one(conn)
+            .await?;
+
+        if account.is_some() {
+            headers.insert(
+                "HX-Redirect",
+                format!("{}/account/{}", prefix, address).parse().unwrap(),
+            );
+            return Ok(headers);
+        }
     }
 
-    headers.insert("HX-Redirect", "/".parse().unwrap());
-    headers
+    headers.insert("HX-Redirect", format!("{}/", prefix).parse().unwrap());
+    Ok(headers)
 }
 
 struct HtmlTemplate<T>(T);
@@ -0,0 +1,207 @@
+use crate::{
+    db_entities::{atxs, layers, transactions},
+    types::Amount,
+    AppState,
+};
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, FromQueryResult,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Statement,
+};
+use std::time::{Duration, Instant};
+use tokio::time::{interval, MissedTickBehavior};
+
+const DEFAULT_REFRESH: Duration = Duration::from_secs(60);
+
+const LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+pub fn install_recorder() -> anyhow::Result<PrometheusHandle> {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Suffix("_duration_seconds".to_string()),
+            LATENCY_BUCKETS,
+        )?
+        .install_recorder()?;
+
+    Ok(handle)
+}
+
+/// Records the latency of every SQL statement sea-orm executes, labelled by
+/// statement kind and the first table it reads from.
+pub fn track_database(conn: &mut DatabaseConnection) {
+    conn.set_metric_callback(|info| {
+        let sql = info.statement.sql.trim_start();
+        let operation = sql
+            .split_whitespace()
+            .next()
+            .unwrap_or("unknown")
+            .to_lowercase();
+        let table = sql
+            .split("FROM")
+            .skip(1)
+            .filter_map(|rest| rest.split_whitespace().next())
+            .find(|table| !table.starts_with('('))
+            .map(|table| {
+                table
+                    .trim_matches(|c: char| !c.is_alphanumeric() && c != '_')
+                    .to_string()
+            })
+            .unwrap_or_else(|| "none".to_string());
+
+        histogram!(
+            "smscan_db_query_duration_seconds",
+            "operation" => operation.clone(),
+            "table" => table.clone(),
+        )
+        .record(info.elapsed.as_secs_f64());
+
+        if info.failed {
+            counter!(
+                "smscan_db_query_errors_total",
+                "operation" => operation,
+                "table" => table,
+            )
+            .increment(1);
+        }
+    });
+}
+
+/// Middleware recording request count and latency per matched route.
+pub async fn track_requests(req: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(req).await;
+
+    let status = response.status().as_u16().to_string();
+    let labels = [("method", method), ("route", route), ("status", status)];
+
+    counter!("smscan_http_requests_total", &labels).increment(1);
+    histogram!("smscan_http_request_duration_seconds", &labels)
+        .record(start.elapsed().as_secs_f64());
+
+    response
+}
+
+#[derive(Debug, FromQueryResult)]
+struct EpochCount {
+    epoch: i32,
+    count: i64,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct Supply {
+    supply: Option<Amount>,
+}
+
+/// Latest balance of every account summed up. Postgres sums `BIGINT`s into a
+/// `NUMERIC`, hence the cast back.
+const TOTAL_SUPPLY_SQL: &str = "SELECT CAST(SUM(balance) AS BIGINT) AS supply FROM accounts a \
+     WHERE layer_updated = (SELECT MAX(layer_updated) FROM accounts b WHERE b.address = a.address)";
+
+pub async fn total_supply(conn: &DatabaseConnection) -> Result<Amount, DbErr> {
+    Ok(Supply::find_by_statement(Statement::from_string(
+        conn.get_database_backend(),
+        TOTAL_SUPPLY_SQL,
+    ))
+    .one(conn)
+    .await?
+    .and_then(|supply| supply.supply)
+    .unwrap_or_default())
+}
+
+pub async fn update_chain_gauges(state: &AppState) -> anyhow::Result<()> {
+    let conn = &state.database;
+    let network = state.network.name.clone();
+
+    let global_state = state.global_state.read().await.clone();
+    gauge!("smscan_node_current_layer", "network" => network.clone())
+        .set(global_state.current_layer as f64);
+    gauge!("smscan_node_current_epoch", "network" => network.clone())
+        .set(global_state.current_epoch as f64);
+    gauge!("smscan_global_state_age_seconds", "network" => network.clone()).set(
+        (Utc::now().naive_utc() - global_state.last_state_fetch).num_milliseconds() as f64 / 1000.0,
+    );
+
+    let last_layer = layers::Entity::find()
+        .order_by_desc(layers::Column::Id)
+        .one(conn)
+        .await?
+        .map(|layer| layer.id)
+        .unwrap_or(0);
+    gauge!("smscan_last_layer", "network" => network.clone()).set(last_layer as f64);
+
+    let processed_layer = layers::Entity::find()
+        .filter(layers::Column::Processed.eq(1))
+        .order_by_desc(layers::Column::Id)
+        .one(conn)
+        .await?
+        .map(|layer| layer.id)
+        .unwrap_or(0);
+    gauge!("smscan_processed_layer", "network" => network.clone()).set(processed_layer as f64);
+
+    let tx_count = transactions::Entity::find().count(conn).await?;
+    gauge!("smscan_transactions", "network" => network.clone()).set(tx_count as f64);
+
+    let atx_counts = atxs::Entity::find()
+        .select_only()
+        .column(atxs::Column::Epoch)
+        .column_as(atxs::Column::Id.count(), "count")
+        .group_by(atxs::Column::Epoch)
+        .into_model::<EpochCount>()
+        .all(conn)
+        .await?;
+    for atx_count in atx_counts {
+        gauge!(
+            "smscan_atxs",
+            "network" => network.clone(),
+            "epoch" => atx_count.epoch.to_string(),
+        )
+        .set(atx_count.count as f64);
+    }
+
+    let supply = total_supply(conn).await?;
+    gauge!("smscan_total_supply_smidge", "network" => network.clone()).set(supply.smidge() as f64);
+
+    Ok(())
+}
+
+/// Keeps the chain gauges up to date from a background task, so scrapes never
+/// run the aggregate queries against the node database themselves.
+pub fn spawn_chain_gauges(state: AppState) {
+    let period = std::env::var("METRICS_REFRESH_SECS")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_REFRESH);
+
+    tokio::spawn(async move {
+        let mut ticker = interval(period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            if let Err(err) = update_chain_gauges(&state).await {
+                eprintln!("Failed to update chain metrics: {}", err);
+            }
+        }
+    });
+}
+
+pub async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    state.metrics.render()
+}
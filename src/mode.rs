@@ -0,0 +1,77 @@
+//! What differs between development builds (`--features dev`) and
+//! production ones: live reload and verbose tracing in development,
+//! compression, security headers and long-lived assets in production.
+//!
+//! Templates are compiled into the binary either way, so in development
+//! `cargo watch` rebuilds on template changes and live reload refreshes the
+//! browser once the server is back.
+
+use axum::{
+    http::{header, HeaderValue},
+    Router,
+};
+use tower_http::{services::ServeDir, set_header::SetResponseHeaderLayer};
+use tracing_subscriber::EnvFilter;
+
+/// Fingerprint of the stylesheet, appended to its URL so browsers fetch it
+/// again after every change.
+pub const ASSETS_VERSION: &str = env!("ASSETS_VERSION");
+
+#[cfg(not(feature = "dev"))]
+const SECURITY_HEADERS: [(header::HeaderName, &str); 4] = [
+    (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+    (header::X_FRAME_OPTIONS, "DENY"),
+    (header::REFERRER_POLICY, "strict-origin-when-cross-origin"),
+    (
+        header::HeaderName::from_static("cross-origin-opener-policy"),
+        "same-origin",
+    ),
+];
+
+/// `RUST_LOG` when set, otherwise debug logs of smscan and every request in
+/// development and info logs in production.
+pub fn tracing_filter() -> EnvFilter {
+    let default = if cfg!(feature = "dev") {
+        "smscan=debug,tower_http=debug,info"
+    } else {
+        "info"
+    };
+    EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default))
+}
+
+/// Wraps the router serving every network.
+#[cfg(feature = "dev")]
+pub fn layers(app: Router) -> Router {
+    app.layer(tower_http::trace::TraceLayer::new_for_http())
+        .layer(tower_livereload::LiveReloadLayer::new())
+}
+
+#[cfg(not(feature = "dev"))]
+pub fn layers(app: Router) -> Router {
+    SECURITY_HEADERS
+        .into_iter()
+        .fold(app, |app, (name, value)| {
+            app.layer(SetResponseHeaderLayer::if_not_present(
+                name,
+                HeaderValue::from_static(value),
+            ))
+        })
+        .layer(tower_http::compression::CompressionLayer::new())
+}
+
+/// The `assets` directory. Production URLs carry [`ASSETS_VERSION`], so
+/// browsers may keep the files for a year.
+pub fn assets() -> Router {
+    let cache_control = if cfg!(feature = "dev") {
+        "no-cache"
+    } else {
+        "public, max-age=31536000, immutable"
+    };
+
+    Router::new()
+        .fallback_service(ServeDir::new("assets"))
+        .layer(SetResponseHeaderLayer::overriding(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static(cache_control),
+        ))
+}
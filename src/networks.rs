@@ -0,0 +1,288 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Router,
+};
+use sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use serde::Deserialize;
+use std::{collections::HashSet, sync::Arc, time::Duration};
+use tokio::time::{sleep, Instant};
+use tower::ServiceExt;
+use tracing::warn;
+
+/// Default database: the node's own `state.sql`, opened read-only.
+const DEFAULT_DATABASE_URL: &str = "sqlite://node-data/state.sql?mode=ro";
+const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(300);
+const CONNECT_RETRY: Duration = Duration::from_secs(5);
+
+/// A network served by this deployment, each backed by its own node.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Network {
+    pub name: String,
+    /// The node's `state.sql`, or a Postgres mirror of it.
+    pub database_url: String,
+    pub node_host: Option<String>,
+    /// The `grpcurl` binary used to query the node.
+    #[serde(default = "default_grpcurl")]
+    pub grpcurl: String,
+    /// Human readable part of the network's bech32 addresses.
+    #[serde(default = "default_hrp")]
+    pub hrp: String,
+    #[serde(default)]
+    pub pool: Pool,
+}
+
+fn default_grpcurl() -> String {
+    "grpcurl".to_string()
+}
+
+fn default_hrp() -> String {
+    "sm".to_string()
+}
+
+/// Connection pool sizing and timeouts. Unset fields fall back to the
+/// `DB_*` variables, then to the sea-orm defaults.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Pool {
+    pub max_connections: Option<u32>,
+    pub min_connections: Option<u32>,
+    pub connect_timeout_secs: Option<u64>,
+    pub acquire_timeout_secs: Option<u64>,
+    pub idle_timeout_secs: Option<u64>,
+}
+
+impl Pool {
+    fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok()?.parse().ok()
+        }
+
+        Pool {
+            max_connections: var("DB_MAX_CONNECTIONS"),
+            min_connections: var("DB_MIN_CONNECTIONS"),
+            connect_timeout_secs: var("DB_CONNECT_TIMEOUT_SECS"),
+            acquire_timeout_secs: var("DB_ACQUIRE_TIMEOUT_SECS"),
+            idle_timeout_secs: var("DB_IDLE_TIMEOUT_SECS"),
+        }
+    }
+
+    fn or(self, defaults: Pool) -> Self {
+        Pool {
+            max_connections: self.max_connections.or(defaults.max_connections),
+            min_connections: self.min_connections.or(defaults.min_connections),
+            connect_timeout_secs: self.connect_timeout_secs.or(defaults.connect_timeout_secs),
+            acquire_timeout_secs: self.acquire_timeout_secs.or(defaults.acquire_timeout_secs),
+            idle_timeout_secs: self.idle_timeout_secs.or(defaults.idle_timeout_secs),
+        }
+    }
+}
+
+impl Network {
+    pub fn connect_options(&self) -> ConnectOptions {
+        let mut options = ConnectOptions::new(self.database_url.clone());
+        if let Some(max) = self.pool.max_connections {
+            options.max_connections(max);
+        }
+        if let Some(min) = self.pool.min_connections {
+            options.min_connections(min);
+        }
+        if let Some(secs) = self.pool.connect_timeout_secs {
+            options.connect_timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = self.pool.acquire_timeout_secs {
+            options.acquire_timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = self.pool.idle_timeout_secs {
+            options.idle_timeout(Duration::from_secs(secs));
+        }
+        options
+    }
+
+    /// Connects to the network's database, retrying while it does not exist
+    /// yet, as when the node container is still starting. Gives up after
+    /// `DB_STARTUP_TIMEOUT_SECS`.
+    pub async fn connect(&self) -> anyhow::Result<DatabaseConnection> {
+        let startup_timeout = std::env::var("DB_STARTUP_TIMEOUT_SECS")
+            .ok()
+            .and_then(|secs| secs.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_STARTUP_TIMEOUT);
+        let deadline = Instant::now() + startup_timeout;
+
+        loop {
+            match Database::connect(self.connect_options()).await {
+                Ok(conn) => return Ok(conn),
+                Err(err) if Instant::now() + CONNECT_RETRY < deadline => {
+                    warn!(
+                        "database of {} is not ready, retrying in {}s: {}",
+                        self.name,
+                        CONNECT_RETRY.as_secs(),
+                        err
+                    );
+                    sleep(CONNECT_RETRY).await;
+                }
+                Err(err) => {
+                    anyhow::bail!("database of {} is not available: {}", self.name, err)
+                }
+            }
+        }
+    }
+}
+
+/// Reads the networks from the JSON file at `NETWORKS_CONFIG`, or describes
+/// the single network given by `NETWORK_NAME`, `DATABASE_URL`, `NODE_HOST`
+/// and `NETWORK_HRP` when it is not set.
+pub fn load_networks() -> anyhow::Result<Vec<Network>> {
+    let networks: Vec<Network> = match std::env::var("NETWORKS_CONFIG") {
+        Ok(path) => serde_json::from_str(&std::fs::read_to_string(&path)?)
+            .map_err(|err| anyhow::anyhow!("invalid networks config {}: {}", path, err))?,
+        Err(_) => vec![Network {
+            name: std::env::var("NETWORK_NAME").unwrap_or_else(|_| "mainnet".to_string()),
+            database_url: std::env::var("DATABASE_URL")
+                .unwrap_or_else(|_| DEFAULT_DATABASE_URL.to_string()),
+            node_host: std::env::var("NODE_HOST").ok(),
+            grpcurl: default_grpcurl(),
+            hrp: std::env::var("NETWORK_HRP").unwrap_or_else(|_| default_hrp()),
+            pool: Pool::default(),
+        }],
+    };
+
+    let defaults = Pool::from_env();
+    let networks = networks
+        .into_iter()
+        .map(|network| Network {
+            pool: network.pool.or(defaults.clone()),
+            ..network
+        })
+        .collect::<Vec<_>>();
+
+    validate(&networks)?;
+    Ok(networks)
+}
+
+fn validate(networks: &[Network]) -> anyhow::Result<()> {
+    if networks.is_empty() {
+        anyhow::bail!("no networks configured");
+    }
+
+    let mut names = HashSet::new();
+    for network in networks {
+        if network.name.is_empty()
+            || !network
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            anyhow::bail!("invalid network name {:?}", network.name);
+        }
+        if !names.insert(network.name.as_str()) {
+            anyhow::bail!("network {} is configured twice", network.name);
+        }
+    }
+
+    Ok(())
+}
+
+/// The explorer of each network by name, the first one being the default.
+struct Apps(Vec<(String, Router)>);
+
+impl Apps {
+    fn find(&self, name: &str) -> Option<&(String, Router)> {
+        self.0.iter().find(|(network, _)| network == name)
+    }
+
+    /// The network named by the first label of the host, so that
+    /// `testnet.example.org` serves `testnet`.
+    fn by_host(&self, headers: &HeaderMap) -> Option<&(String, Router)> {
+        let host = headers.get(header::HOST)?.to_str().ok()?;
+        self.find(host.split('.').next()?)
+    }
+}
+
+tokio::task_local! {
+    static PREFIX: String;
+}
+
+/// Path prefix of the network serving the current request, `/net/<name>`
+/// when it was picked by path and empty otherwise. Templates put it in front
+/// of every link so that browsing stays on the same network.
+pub fn prefix() -> String {
+    PREFIX.try_with(Clone::clone).unwrap_or_default()
+}
+
+/// Serves every network's explorer from one listener. `/net/<name>/...`
+/// serves the named network, otherwise the subdomain picks it, then the
+/// first one is used.
+pub fn networks_router(apps: Vec<(String, Router)>) -> Router {
+    Router::new()
+        .fallback(dispatch)
+        .with_state(Arc::new(Apps(apps)))
+}
+
+async fn dispatch(State(apps): State<Arc<Apps>>, mut req: Request) -> Response {
+    let Some(rest) = req.uri().path().strip_prefix("/net/") else {
+        let (_, app) = apps.by_host(req.headers()).unwrap_or(&apps.0[0]);
+        return app.clone().oneshot(req).await.into_response();
+    };
+
+    let (name, path) = rest
+        .find('/')
+        .map_or((rest, "/"), |slash| rest.split_at(slash));
+    let Some((name, app)) = apps.find(name) else {
+        return (StatusCode::NOT_FOUND, format!("Network {} not found", name)).into_response();
+    };
+
+    let path_and_query = match req.uri().query() {
+        Some(query) => format!("{}?{}", path, query),
+        None => path.to_string(),
+    };
+    match path_and_query.parse::<Uri>() {
+        Ok(uri) => *req.uri_mut() = uri,
+        Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+    }
+
+    PREFIX
+        .scope(format!("/net/{}", name), app.clone().oneshot(req))
+        .await
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn networks_config_parses_pool_options() {
+        let networks: Vec<Network> = serde_json::from_str(
+            r#"[
+                {"name": "mainnet", "database_url": "postgres://replica/smscan",
+                 "pool": {"max_connections": 20, "acquire_timeout_secs": 3}},
+                {"name": "testnet", "database_url": "sqlite://testnet.sql",
+                 "node_host": "node:9092", "hrp": "stest"}
+            ]"#,
+        )
+        .unwrap();
+        assert!(validate(&networks).is_ok());
+        assert_eq!(networks[0].hrp, "sm");
+        assert_eq!(networks[1].pool, Pool::default());
+
+        let pool = networks[0].pool.clone().or(Pool {
+            max_connections: Some(5),
+            idle_timeout_secs: Some(60),
+            ..Pool::default()
+        });
+        assert_eq!(pool.max_connections, Some(20));
+        assert_eq!(pool.idle_timeout_secs, Some(60));
+
+        let options = networks[0].connect_options();
+        assert_eq!(options.get_url(), "postgres://replica/smscan");
+        assert_eq!(options.get_max_connections(), Some(20));
+        assert_eq!(options.get_acquire_timeout(), Some(Duration::from_secs(3)));
+        assert_eq!(options.get_connect_timeout(), None);
+
+        let twice = vec![networks[0].clone(), networks[0].clone()];
+        assert!(validate(&twice).is_err());
+        assert!(validate(&[]).is_err());
+    }
+}
@@ -1,5 +1,12 @@
 use crate::{
-    db_entities::{accounts, rewards, transactions},
+    components::checkpoint::restore_layer,
+    db_entities::{accounts, atxs, rewards, transactions, transactions_results_addresses},
+    error::AppError,
+    pages::mempool,
+    pagination::{fetch_page, Cursor, PageQuery, Pagination, NULL_KEY},
+    types::{Address, LayerId},
+    view_models::{Account, Atx, Creation, Reward, Spawn, Transaction},
+    vm::{AccountState, RawTx},
     AppState, HtmlTemplate,
 };
 use askama::Template;
@@ -9,9 +16,13 @@ use axum::{
     routing::get,
     Router,
 };
-use chrono::NaiveDateTime;
-use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
-use serde::{Deserialize, Serialize};
+use sea_orm::{
+    sea_query, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect,
+};
+
+/// Oldest transactions of an account searched for the one spawning it.
+const SPAWN_SEARCH_LIMIT: u64 = 50;
 
 pub fn account_route() -> Router<AppState> {
     Router::new()
@@ -19,6 +30,7 @@ pub fn account_route() -> Router<AppState> {
         .route("/:id/account", get(account_handler))
         .route("/:id/transactions", get(transactions_handler))
         .route("/:id/rewards", get(rewards_handler))
+        .route("/:id/atxs", get(atxs_handler))
 }
 
 #[derive(Template)]
@@ -35,241 +47,204 @@ async fn page(Path(id): Path<String>) -> impl IntoResponse {
 #[derive(Template)]
 #[template(path = "components/account/account_tab.html")]
 struct AccountTabTemplate {
-    address: String,
-    balance: f64,
-    next_nonce: i64,
-    template: String,
-    state: String,
+    account: Account,
+    bech32: String,
+    creation: Creation,
+    pending: u64,
+    current_layer: LayerId,
 }
 
 async fn account_handler(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> impl IntoResponse {
+) -> Result<impl IntoResponse, AppError> {
     let conn = &state.database;
 
+    let address = id.parse::<Address>()?;
     let db_account = accounts::Entity::find()
-        .filter(accounts::Column::Address.eq(hex::decode(id).unwrap()))
-        .all(conn)
-        .await
-        .unwrap();
-
-    let layer = AccountTabTemplate {
-        address: db_account
-            .first()
-            .unwrap()
-            .address
-            .iter()
-            .map(|byte| format!("{:02x}", byte))
-            .collect(),
-        balance: db_account.first().unwrap().balance as f64 / 1_000_000_000.0,
-        next_nonce: db_account.first().unwrap().next_nonce,
-        template: db_account
-            .first()
-            .unwrap()
-            .template
-            .as_ref()
-            .unwrap_or(&vec![])
-            .iter()
-            .map(|byte| format!("{:02x}", byte))
-            .collect(),
-        state: db_account
-            .first()
-            .unwrap()
-            .state
-            .as_ref()
-            .unwrap_or(&vec![])
-            .iter()
-            .map(|byte| format!("{:02x}", byte))
-            .collect(),
+        .filter(accounts::Column::Address.eq(address.clone()))
+        .order_by_desc(accounts::Column::LayerUpdated)
+        .one(conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Account {} not found", id)))?;
+
+    let creation = creation(conn, &address, &db_account).await?;
+    let pending = mempool::pending()
+        .filter(transactions::Column::Principal.eq(address.clone()))
+        .count(conn)
+        .await?;
+    let current_layer = state.global_state.read().await.current_layer;
+    let template = AccountTabTemplate {
+        bech32: address.to_bech32(&state.network.hrp),
+        account: Account::from(db_account),
+        creation,
+        pending,
+        current_layer: LayerId(current_layer.try_into().unwrap_or(i32::MAX)),
     };
-
-    HtmlTemplate(layer)
+    Ok(HtmlTemplate(template))
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct TransactionTabTemplate {
-    tx: String,
-    header: String,
-    result: String,
-    layer: i32,
-    block: String,
-    principal: String,
-    principal_short: String,
-    nonce: u64,
-    timestamp: String,
+/// Finds the first layer the account appears in and the transaction that
+/// spawned it, either sent by the account itself or by the multisig or
+/// vesting account that spawned it.
+async fn creation(
+    conn: &DatabaseConnection,
+    address: &Address,
+    account: &accounts::Model,
+) -> Result<Creation, DbErr> {
+    let first_seen = accounts::Entity::find()
+        .filter(accounts::Column::Address.eq(address.clone()))
+        .order_by_asc(accounts::Column::LayerUpdated)
+        .one(conn)
+        .await?
+        .map(|first| LayerId(first.layer_updated as i32));
+
+    let (Some(template), Some(state)) = (&account.template, &account.state) else {
+        return Ok(Creation {
+            first_seen,
+            spawn: None,
+        });
+    };
+
+    let touched = sea_query::Query::select()
+        .column(transactions_results_addresses::Column::Tid)
+        .from(transactions_results_addresses::Entity)
+        .and_where(transactions_results_addresses::Column::Address.eq(address.clone()))
+        .to_owned();
+
+    let candidates = transactions::Entity::find()
+        .filter(transactions::Column::Layer.is_not_null())
+        .filter(
+            Condition::any()
+                .add(transactions::Column::Principal.eq(address.clone()))
+                .add(transactions::Column::Id.in_subquery(touched)),
+        )
+        .order_by_asc(transactions::Column::Layer)
+        .order_by_asc(transactions::Column::Id)
+        .limit(SPAWN_SEARCH_LIMIT)
+        .all(conn)
+        .await?;
+
+    let spawn = candidates.into_iter().find_map(|tx| {
+        let raw = RawTx::decode(tx.tx.as_deref()?)?;
+        if !raw.spawns(template, state) {
+            return None;
+        }
+        Spawn::from_tx(address, tx, raw)
+    });
+
+    // The spawn can predate the oldest account row the node kept.
+    let first_seen = first_seen
+        .into_iter()
+        .chain(spawn.as_ref().and_then(|spawn| spawn.layer))
+        .min();
+
+    Ok(Creation { first_seen, spawn })
 }
 
 #[derive(Template)]
 #[template(path = "components/account/transactions_tab.html")]
 struct TransactionsTabTemplate {
-    id: String,
-    txs: Vec<TransactionTabTemplate>,
-    txs_count: u64,
-    skip: u64,
-}
-
-#[derive(Deserialize)]
-struct Pagination {
-    skip: Option<u64>,
+    txs: Vec<Transaction>,
+    pagination: Pagination,
 }
 
 async fn transactions_handler(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    pagination: Query<Pagination>,
-) -> impl IntoResponse {
+    Query(query): Query<PageQuery>,
+) -> Result<impl IntoResponse, AppError> {
     let conn = &state.database;
 
-    let db_transactions = transactions::Entity::find()
-        .filter(transactions::Column::Principal.eq(hex::decode(id.clone()).unwrap()))
-        .order_by_desc(transactions::Column::Layer)
-        .offset(pagination.skip)
-        .limit(20)
-        .all(conn)
-        .await
-        .unwrap();
-
-    let txs = db_transactions
-        .iter()
-        .map(|tx| TransactionTabTemplate {
-            tx: tx
-                .tx
-                .clone()
-                .unwrap()
-                .iter()
-                .map(|byte| format!("{:02x}", byte))
-                .collect(),
-            header: tx
-                .header
-                .clone()
-                .unwrap()
-                .iter()
-                .map(|byte| format!("{:02x}", byte))
-                .collect(),
-            result: tx
-                .result
-                .clone()
-                .unwrap()
-                .iter()
-                .map(|byte| format!("{:02x}", byte))
-                .collect(),
-            layer: tx.layer.unwrap(),
-            block: tx
-                .block
-                .as_ref()
-                .unwrap_or(&vec![])
-                .iter()
-                .map(|byte| format!("{:02x}", byte))
-                .collect(),
-            principal: tx
-                .principal
-                .as_ref()
-                .unwrap_or(&vec![])
-                .iter()
-                .map(|byte| format!("{:02x}", byte))
-                .collect(),
-            principal_short: tx
-                .principal
-                .as_ref()
-                .unwrap_or(&vec![])
-                .iter()
-                .map(|byte| format!("{:02x}", byte))
-                .rev()
-                .take(6)
-                .rev()
-                .collect(),
-            nonce: {
-                let nonce_vec = tx.nonce.clone().unwrap();
-
-                let nonce_array = [
-                    nonce_vec[0],
-                    nonce_vec[1],
-                    nonce_vec[2],
-                    nonce_vec[3],
-                    nonce_vec[4],
-                    nonce_vec[5],
-                    nonce_vec[6],
-                    nonce_vec[7],
-                ];
-
-                u64::from_be_bytes(nonce_array)
-            },
-            timestamp: NaiveDateTime::from_timestamp_millis(tx.timestamp / 1000000)
-                .unwrap()
-                .to_string(),
-        })
-        .collect();
-
+    let page = fetch_page(
+        conn,
+        transactions::Entity::find()
+            .filter(transactions::Column::Principal.eq(id.parse::<Address>()?)),
+        transactions::Column::Layer,
+        Some(transactions::Column::Id),
+        &query,
+        |tx| Cursor::new(tx.layer.unwrap_or(NULL_KEY), &tx.id),
+    )
+    .await?
+    .map(Transaction::from);
+
+    let pagination = Pagination::new(
+        format!("/account/{}/transactions", id),
+        "account-transactions",
+        &page,
+    )
+    .with_checkpoint(restore_layer(conn).await?);
     let template = TransactionsTabTemplate {
-        id,
-        txs,
-        txs_count: db_transactions.len() as u64,
-        skip: pagination.skip.unwrap_or(0) + 20,
+        txs: page.items,
+        pagination,
     };
-    HtmlTemplate(template)
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct RewardTabTemplate {
-    coinbase: String,
-    coinbase_short: String,
-    layer: i32,
-    total_reward: f32,
-    layer_reward: f32,
+    Ok(HtmlTemplate(template))
 }
 
 #[derive(Template)]
 #[template(path = "components/account/rewards_tab.html")]
 struct RewardsTabTemplate {
-    id: String,
-    rewards: Vec<RewardTabTemplate>,
-    rewards_count: u64,
-    skip: u64,
+    rewards: Vec<Reward>,
+    pagination: Pagination,
 }
 
 async fn rewards_handler(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    pagination: Query<Pagination>,
-) -> impl IntoResponse {
+    Query(query): Query<PageQuery>,
+) -> Result<impl IntoResponse, AppError> {
     let conn = &state.database;
-    let db_rewards = rewards::Entity::find()
-        .filter(rewards::Column::Coinbase.eq(hex::decode(id.clone()).unwrap()))
-        .order_by_desc(rewards::Column::Layer)
-        .offset(pagination.skip)
-        .limit(20)
-        .all(conn)
-        .await
-        .unwrap();
-
-    let rewards = db_rewards
-        .iter()
-        .map(|reward| RewardTabTemplate {
-            coinbase: reward
-                .coinbase
-                .iter()
-                .map(|byte| format!("{:02x}", byte))
-                .collect(),
-            coinbase_short: reward
-                .coinbase
-                .iter()
-                .map(|byte| format!("{:02x}", byte))
-                .rev()
-                .take(6)
-                .rev()
-                .collect(),
-            layer: reward.layer,
-            total_reward: (reward.total_reward.unwrap_or(0) as f32 / 1_000_000_000.0),
-            layer_reward: (reward.layer_reward.unwrap_or(0) as f32 / 1_000_000_000.0),
-        })
-        .collect();
 
+    let page = fetch_page(
+        conn,
+        rewards::Entity::find().filter(rewards::Column::Coinbase.eq(id.parse::<Address>()?)),
+        rewards::Column::Layer,
+        Some(rewards::Column::Coinbase),
+        &query,
+        |reward| Cursor::new(reward.layer, &reward.coinbase),
+    )
+    .await?
+    .map(Reward::from);
+
+    let pagination = Pagination::new(format!("/account/{}/rewards", id), "account-rewards", &page)
+        .with_checkpoint(restore_layer(conn).await?);
     let template = RewardsTabTemplate {
-        id,
-        rewards,
-        rewards_count: db_rewards.len() as u64,
-        skip: pagination.skip.unwrap_or(0) + 20,
+        rewards: page.items,
+        pagination,
     };
-    HtmlTemplate(template)
+    Ok(HtmlTemplate(template))
+}
+
+#[derive(Template)]
+#[template(path = "components/account/atxs_tab.html")]
+struct AtxsTabTemplate {
+    atxs: Vec<Atx>,
+    pagination: Pagination,
+}
+
+async fn atxs_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let conn = &state.database;
+
+    let page = fetch_page(
+        conn,
+        atxs::Entity::find().filter(atxs::Column::Coinbase.eq(id.parse::<Address>()?)),
+        atxs::Column::Epoch,
+        Some(atxs::Column::Id),
+        &query,
+        |atx| Cursor::new(atx.epoch, &atx.id),
+    )
+    .await?
+    .map(Atx::from);
+
+    let pagination = Pagination::new(format!("/account/{}/atxs", id), "account-atxs", &page)
+        .with_checkpoint(restore_layer(conn).await?);
+    let template = AtxsTabTemplate {
+        atxs: page.items,
+        pagination,
+    };
+    Ok(HtmlTemplate(template))
 }
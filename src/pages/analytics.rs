@@ -0,0 +1,213 @@
+use crate::{
+    db_entities::atxs,
+    error::{ApiError, AppError},
+    types::EpochId,
+    view_models::{Chart, EpochNetwork, UnitsDistribution},
+    AppState, HtmlTemplate,
+};
+use askama::Template;
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, FromQueryResult,
+    QueryFilter, QueryOrder, QuerySelect,
+};
+use serde::Deserialize;
+
+pub fn analytics_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(page))
+        .route("/network", get(network_handler))
+        .route("/distribution", get(distribution_handler))
+}
+
+pub fn analytics_api_routes() -> Router<AppState> {
+    Router::new()
+        .route("/network", get(api_network_handler))
+        .route("/distribution", get(api_distribution_handler))
+}
+
+#[derive(Debug, FromQueryResult)]
+struct EpochTotals {
+    epoch: i32,
+    atxs: i64,
+    smeshers: i64,
+    units: Option<i64>,
+}
+
+/// Network size of every epoch with ATXs, oldest first.
+async fn network(conn: &DatabaseConnection) -> Result<Vec<EpochNetwork>, DbErr> {
+    let totals = atxs::Entity::find()
+        .select_only()
+        .column(atxs::Column::Epoch)
+        .column_as(atxs::Column::Id.count(), "atxs")
+        .column_as(Expr::col(atxs::Column::Pubkey).count_distinct(), "smeshers")
+        .column_as(atxs::Column::EffectiveNumUnits.sum(), "units")
+        .group_by(atxs::Column::Epoch)
+        .order_by_asc(atxs::Column::Epoch)
+        .into_model::<EpochTotals>()
+        .all(conn)
+        .await?;
+
+    Ok(totals
+        .into_iter()
+        .map(|totals| {
+            EpochNetwork::new(
+                EpochId(totals.epoch + 1),
+                totals.atxs.max(0) as u64,
+                totals.smeshers.max(0) as u64,
+                totals.units.unwrap_or(0).max(0) as u64,
+            )
+        })
+        .collect())
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DistributionQuery {
+    epoch: Option<EpochId>,
+}
+
+/// Units committed per smesher in `epoch`, the latest one by default.
+async fn distribution(
+    conn: &DatabaseConnection,
+    epoch: Option<EpochId>,
+) -> Result<Option<UnitsDistribution>, DbErr> {
+    let published = match epoch {
+        Some(epoch) => match epoch.previous() {
+            Some(published) => published.0,
+            None => return Ok(None),
+        },
+        None => {
+            let latest = atxs::Entity::find()
+                .select_only()
+                .column_as(atxs::Column::Epoch.max(), "epoch")
+                .into_tuple::<Option<i32>>()
+                .one(conn)
+                .await?
+                .flatten();
+            match latest {
+                Some(latest) => latest,
+                None => return Ok(None),
+            }
+        }
+    };
+
+    // Smeshers caught publishing twice count once, with all their units.
+    let units = atxs::Entity::find()
+        .select_only()
+        .column_as(atxs::Column::EffectiveNumUnits.sum(), "units")
+        .filter(atxs::Column::Epoch.eq(published))
+        .group_by(atxs::Column::Pubkey)
+        .into_tuple::<i64>()
+        .all(conn)
+        .await?;
+    if units.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(UnitsDistribution::new(
+        EpochId(published + 1),
+        units.into_iter().map(|units| units.max(0) as u64),
+    )))
+}
+
+#[derive(Template)]
+#[template(path = "pages/analytics.html")]
+struct AnalyticsTemplate;
+
+async fn page() -> impl IntoResponse {
+    let template = AnalyticsTemplate {};
+    HtmlTemplate(template)
+}
+
+#[derive(Template)]
+#[template(path = "components/analytics/network.html")]
+struct NetworkTemplate {
+    charts: Vec<Chart>,
+}
+
+async fn network_handler(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let epochs = network(&state.database).await?;
+    let chart = |title: &str, value: fn(&EpochNetwork) -> f64, shown: fn(f64) -> String| {
+        Chart::new(
+            title,
+            epochs.iter().map(|epoch| {
+                let value = value(epoch);
+                (format!("Epoch {}", epoch.epoch), value, shown(value))
+            }),
+        )
+    };
+
+    let template = NetworkTemplate {
+        charts: vec![
+            chart("Network size", EpochNetwork::tib, |tib| {
+                format!("{:.2} TiB", tib)
+            }),
+            chart(
+                "Smeshers",
+                |epoch| epoch.smeshers as f64,
+                |smeshers| smeshers.to_string(),
+            ),
+            chart(
+                "Average units per smesher",
+                |epoch| epoch.average_units,
+                |units| format!("{:.1}", units),
+            ),
+        ],
+    };
+    Ok(HtmlTemplate(template))
+}
+
+#[derive(Template)]
+#[template(path = "components/analytics/distribution.html")]
+struct DistributionTemplate {
+    epoch: Option<EpochId>,
+    charts: Vec<Chart>,
+}
+
+async fn distribution_handler(
+    State(state): State<AppState>,
+    Query(query): Query<DistributionQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let distribution = distribution(&state.database, query.epoch).await?;
+
+    let template = DistributionTemplate {
+        epoch: distribution.as_ref().map(|distribution| distribution.epoch),
+        charts: distribution
+            .into_iter()
+            .map(|distribution| {
+                Chart::new(
+                    "Units per smesher",
+                    distribution.buckets.into_iter().map(|bucket| {
+                        (
+                            format!("{}–{} units", bucket.min, bucket.max),
+                            bucket.smeshers as f64,
+                            bucket.smeshers.to_string(),
+                        )
+                    }),
+                )
+            })
+            .collect(),
+    };
+    Ok(HtmlTemplate(template))
+}
+
+async fn api_network_handler(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<EpochNetwork>>, ApiError> {
+    Ok(Json(network(&state.database).await?))
+}
+
+async fn api_distribution_handler(
+    State(state): State<AppState>,
+    Query(query): Query<DistributionQuery>,
+) -> Result<Json<UnitsDistribution>, ApiError> {
+    let distribution = distribution(&state.database, query.epoch)
+        .await?
+        .ok_or_else(|| AppError::NotFound("No ATXs for this epoch".to_string()))?;
+    Ok(Json(distribution))
+}
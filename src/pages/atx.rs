@@ -0,0 +1,74 @@
+use crate::{
+    db_entities::atxs,
+    error::AppError,
+    pages::{beacons::find_beacon, malfeasance::find_malfeasance, poets::find_round},
+    types::{AtxId, Beacon, PoetRef},
+    view_models::{Atx, MaliciousIdentity, PoetRound},
+    vm::{atx_poet_ref, Offense},
+    AppState, HtmlTemplate,
+};
+use askama::Template;
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+pub fn atx_routes() -> Router<AppState> {
+    Router::new()
+        .route("/:id", get(page))
+        .route("/:id/atx", get(atx_handler))
+}
+
+#[derive(Template)]
+#[template(path = "pages/atx.html")]
+struct AtxPageTemplate {
+    id: String,
+}
+
+async fn page(Path(id): Path<String>) -> impl IntoResponse {
+    let template = AtxPageTemplate { id };
+    HtmlTemplate(template)
+}
+
+#[derive(Template)]
+#[template(path = "components/atx/atx_tab.html")]
+struct AtxTabTemplate {
+    atx: Atx,
+    beacon: Option<Beacon>,
+    poet_ref: Option<PoetRef>,
+    round: Option<PoetRound>,
+    malicious: Option<MaliciousIdentity>,
+}
+
+async fn atx_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let db_atx = atxs::Entity::find()
+        .filter(atxs::Column::Id.eq(id.parse::<AtxId>()?))
+        .one(&state.database)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("ATX {} not found", id)))?;
+
+    let poet_ref = db_atx.atx.as_deref().and_then(atx_poet_ref);
+    let round = match &poet_ref {
+        Some(poet_ref) => find_round(&state.database, poet_ref).await?,
+        None => None,
+    };
+
+    let atx = Atx::from(db_atx);
+    let beacon = find_beacon(&state, atx.target_epoch()).await?;
+    let malicious = find_malfeasance(&state.database, &atx.pubkey).await?;
+
+    let template = AtxTabTemplate {
+        atx,
+        beacon,
+        poet_ref,
+        round,
+        malicious,
+    };
+    Ok(HtmlTemplate(template))
+}
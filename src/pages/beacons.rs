@@ -0,0 +1,117 @@
+use crate::{
+    db_entities::beacons,
+    error::{ApiError, AppError},
+    types::{Beacon, EpochId},
+    view_models::EpochBeacon,
+    AppState, HtmlTemplate,
+};
+use askama::Template;
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use sea_orm::{DbErr, EntityTrait, QueryOrder};
+use std::collections::HashMap;
+
+pub fn beacons_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(page))
+        .route("/list", get(list_handler))
+}
+
+pub fn beacons_api_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(api_list_handler))
+        .route("/:epoch", get(api_epoch_handler))
+}
+
+/// Beacon of `epoch`, if the node stored one.
+pub async fn find_beacon(state: &AppState, epoch: EpochId) -> Result<Option<Beacon>, DbErr> {
+    Ok(beacons::Entity::find_by_id(epoch.0)
+        .one(&state.database)
+        .await?
+        .and_then(|beacon| beacon.beacon.map(Beacon)))
+}
+
+/// Every epoch from the first stored beacon up to the current epoch, newest
+/// first. Epochs the node has no beacon row for are listed as missing too.
+async fn epoch_beacons(state: &AppState) -> Result<Vec<EpochBeacon>, DbErr> {
+    let stored: HashMap<EpochId, EpochBeacon> = beacons::Entity::find()
+        .order_by_asc(beacons::Column::Epoch)
+        .all(&state.database)
+        .await?
+        .into_iter()
+        .map(|beacon| (EpochId(beacon.epoch), EpochBeacon::from(beacon)))
+        .collect();
+
+    let Some(first) = stored.keys().min().copied() else {
+        return Ok(vec![]);
+    };
+    let current = state.global_state.read().await.current_epoch;
+    let last = stored
+        .keys()
+        .max()
+        .copied()
+        .unwrap_or(first)
+        .max(EpochId(current.try_into().unwrap_or(i32::MAX)));
+
+    Ok((first.0..=last.0)
+        .rev()
+        .map(|epoch| {
+            stored.get(&EpochId(epoch)).cloned().unwrap_or(EpochBeacon {
+                epoch: EpochId(epoch),
+                beacon: None,
+            })
+        })
+        .collect())
+}
+
+#[derive(Template)]
+#[template(path = "pages/beacons.html")]
+struct BeaconsTemplate;
+
+async fn page() -> impl IntoResponse {
+    let template = BeaconsTemplate {};
+    HtmlTemplate(template)
+}
+
+#[derive(Template)]
+#[template(path = "components/beacons/beacons_list.html")]
+struct BeaconsListTemplate {
+    beacons: Vec<EpochBeacon>,
+    missing: usize,
+}
+
+async fn list_handler(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let beacons = epoch_beacons(&state).await?;
+
+    let template = BeaconsListTemplate {
+        missing: beacons
+            .iter()
+            .filter(|beacon| beacon.beacon.is_none())
+            .count(),
+        beacons,
+    };
+    Ok(HtmlTemplate(template))
+}
+
+async fn api_list_handler(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<EpochBeacon>>, ApiError> {
+    Ok(Json(epoch_beacons(&state).await?))
+}
+
+async fn api_epoch_handler(
+    State(state): State<AppState>,
+    Path(epoch): Path<EpochId>,
+) -> Result<Json<EpochBeacon>, ApiError> {
+    let beacon = epoch_beacons(&state)
+        .await?
+        .into_iter()
+        .find(|beacon| beacon.epoch == epoch)
+        .ok_or_else(|| AppError::NotFound(format!("Epoch {} not found", epoch)))?;
+
+    Ok(Json(beacon))
+}
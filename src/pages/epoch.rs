@@ -0,0 +1,268 @@
+use crate::{
+    db_entities::{activesets, atxs},
+    error::AppError,
+    pages::beacons::find_beacon,
+    types::{AtxId, Beacon, EpochId, LayerId, NodeId},
+    view_models::Atx,
+    vm::ActiveSet,
+    AppState, HtmlTemplate,
+};
+use askama::Template;
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, FromQueryResult, QueryFilter, QuerySelect,
+};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// ATX ids looked up per query, below SQLite's bound parameter limit.
+const ATX_LOOKUP_CHUNK: usize = 500;
+/// Smeshers listed on each side of an active set diff.
+const DIFF_LIMIT: usize = 20;
+
+pub fn epoch_routes() -> Router<AppState> {
+    Router::new()
+        .route("/:id", get(page))
+        .route("/:id/epoch", get(epoch_handler))
+        .route("/:id/activeset", get(active_set_handler))
+        .route("/:id/activeset/check", get(membership_handler))
+}
+
+#[derive(Template)]
+#[template(path = "pages/epoch.html")]
+struct EpochTemplate {
+    id: EpochId,
+    prev: Option<EpochId>,
+    next: EpochId,
+}
+
+async fn page(Path(id): Path<EpochId>) -> impl IntoResponse {
+    let template = EpochTemplate {
+        id,
+        prev: id.previous(),
+        next: EpochId(id.0.saturating_add(1)),
+    };
+    HtmlTemplate(template)
+}
+
+/// Epochs count from zero.
+fn check_epoch(epoch: EpochId) -> Result<(), AppError> {
+    if epoch.0 < 0 {
+        return Err(AppError::NotFound(format!("Epoch {} not found", epoch)));
+    }
+    Ok(())
+}
+
+#[derive(Debug, FromQueryResult)]
+struct AtxTotals {
+    count: i64,
+    units: Option<i64>,
+}
+
+#[derive(Template)]
+#[template(path = "components/epoch/epoch_tab.html")]
+struct EpochTabTemplate {
+    epoch: EpochId,
+    beacon: Option<Beacon>,
+    layers: Option<(LayerId, LayerId)>,
+    atxs: i64,
+    units: i64,
+}
+
+async fn epoch_handler(
+    State(state): State<AppState>,
+    Path(epoch): Path<EpochId>,
+) -> Result<impl IntoResponse, AppError> {
+    check_epoch(epoch)?;
+    let beacon = find_beacon(&state, epoch).await?;
+
+    // ATXs for an epoch are published during the one before.
+    let totals = match epoch.previous() {
+        Some(published) => {
+            atxs::Entity::find()
+                .select_only()
+                .column_as(atxs::Column::Id.count(), "count")
+                .column_as(atxs::Column::EffectiveNumUnits.sum(), "units")
+                .filter(atxs::Column::Epoch.eq(published.0))
+                .into_model::<AtxTotals>()
+                .one(&state.database)
+                .await?
+        }
+        None => None,
+    };
+
+    let epoch_num_layers = state.global_state.read().await.epoch_num_layers;
+    let layers = i32::try_from(epoch_num_layers)
+        .ok()
+        .and_then(|epoch_num_layers| epoch.layers(epoch_num_layers));
+
+    let template = EpochTabTemplate {
+        epoch,
+        beacon,
+        layers,
+        atxs: totals.as_ref().map(|totals| totals.count).unwrap_or(0),
+        units: totals.and_then(|totals| totals.units).unwrap_or(0),
+    };
+    Ok(HtmlTemplate(template))
+}
+
+/// Members of an epoch's active set. Nodes can store several sets for the
+/// same epoch, one per ballot that referenced it, so they are merged.
+struct EpochActiveSet {
+    atxs: BTreeSet<AtxId>,
+    /// Stored members by smesher; ATXs the node never received are missing.
+    smeshers: BTreeMap<NodeId, Atx>,
+}
+
+impl EpochActiveSet {
+    fn weight(&self) -> u64 {
+        self.smeshers.values().map(Atx::weight).sum()
+    }
+}
+
+async fn find_active_set(
+    conn: &DatabaseConnection,
+    epoch: EpochId,
+) -> Result<Option<EpochActiveSet>, DbErr> {
+    let rows = activesets::Entity::find()
+        .filter(activesets::Column::Epoch.eq(epoch.0))
+        .all(conn)
+        .await?;
+    if rows.is_empty() {
+        return Ok(None);
+    }
+
+    let atxs: BTreeSet<AtxId> = rows
+        .iter()
+        .filter_map(|row| ActiveSet::decode(row.active_set.as_deref()?))
+        .flat_map(|set| set.atxs)
+        .collect();
+
+    let ids: Vec<AtxId> = atxs.iter().cloned().collect();
+    let mut smeshers = BTreeMap::new();
+    for chunk in ids.chunks(ATX_LOOKUP_CHUNK) {
+        let found = atxs::Entity::find()
+            .filter(atxs::Column::Id.is_in(chunk.iter().cloned()))
+            .all(conn)
+            .await?;
+        for atx in found.into_iter().map(Atx::from) {
+            smeshers.insert(atx.pubkey.clone(), atx);
+        }
+    }
+
+    Ok(Some(EpochActiveSet { atxs, smeshers }))
+}
+
+/// Smeshers that joined and left the active set since the previous epoch.
+struct ActiveSetDiff {
+    previous: EpochId,
+    joined: Vec<NodeId>,
+    joined_total: usize,
+    left: Vec<NodeId>,
+    left_total: usize,
+}
+
+impl ActiveSetDiff {
+    fn new(previous: EpochId, before: &EpochActiveSet, after: &EpochActiveSet) -> Self {
+        let joined: Vec<NodeId> = after
+            .smeshers
+            .keys()
+            .filter(|smesher| !before.smeshers.contains_key(smesher))
+            .cloned()
+            .collect();
+        let left: Vec<NodeId> = before
+            .smeshers
+            .keys()
+            .filter(|smesher| !after.smeshers.contains_key(smesher))
+            .cloned()
+            .collect();
+
+        ActiveSetDiff {
+            previous,
+            joined_total: joined.len(),
+            joined: joined.into_iter().take(DIFF_LIMIT).collect(),
+            left_total: left.len(),
+            left: left.into_iter().take(DIFF_LIMIT).collect(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "components/epoch/active_set_tab.html")]
+struct ActiveSetTabTemplate {
+    epoch: EpochId,
+    set: Option<EpochActiveSet>,
+    diff: Option<ActiveSetDiff>,
+}
+
+async fn active_set_handler(
+    State(state): State<AppState>,
+    Path(epoch): Path<EpochId>,
+) -> Result<impl IntoResponse, AppError> {
+    let conn = &state.database;
+
+    check_epoch(epoch)?;
+    let set = find_active_set(conn, epoch).await?;
+    let diff = match (&set, epoch.previous()) {
+        (Some(set), Some(previous)) => find_active_set(conn, previous)
+            .await?
+            .map(|before| ActiveSetDiff::new(previous, &before, set)),
+        _ => None,
+    };
+
+    let template = ActiveSetTabTemplate { epoch, set, diff };
+    Ok(HtmlTemplate(template))
+}
+
+#[derive(Deserialize)]
+struct MembershipQuery {
+    id: String,
+}
+
+/// How the checked id relates to the active set.
+enum Membership {
+    Atx,
+    Smesher(AtxId),
+    Absent,
+    NoActiveSet,
+}
+
+#[derive(Template)]
+#[template(path = "components/epoch/membership.html")]
+struct MembershipTemplate {
+    epoch: EpochId,
+    id: String,
+    membership: Membership,
+}
+
+/// Checks whether an id, either an ATX or a smesher since both are 32 bytes,
+/// is part of the epoch's active set.
+async fn membership_handler(
+    State(state): State<AppState>,
+    Path(epoch): Path<EpochId>,
+    Query(query): Query<MembershipQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let id = query.id.trim().to_lowercase();
+    let atx = id.parse::<AtxId>()?;
+
+    let membership = match find_active_set(&state.database, epoch).await? {
+        None => Membership::NoActiveSet,
+        Some(set) if set.atxs.contains(&atx) => Membership::Atx,
+        Some(set) => match set.smeshers.get(&NodeId(atx.0)) {
+            Some(member) => Membership::Smesher(member.id.clone()),
+            None => Membership::Absent,
+        },
+    };
+
+    let template = MembershipTemplate {
+        epoch,
+        id,
+        membership,
+    };
+    Ok(HtmlTemplate(template))
+}
@@ -0,0 +1,255 @@
+use crate::{
+    components::global_state::current_global_state,
+    db_entities::{certificates, layers},
+    error::AppError,
+    types::LayerId,
+    AppState, HtmlTemplate,
+};
+use askama::Template;
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+const DEFAULT_WINDOW: i32 = 100;
+const MAX_WINDOW: i32 = 1000;
+const MAX_ISSUES: usize = 20;
+const WEAK_COIN_LAYERS: usize = 30;
+
+pub fn health_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(page))
+        .route("/status", get(status_handler))
+}
+
+#[derive(Template)]
+#[template(path = "pages/health.html")]
+struct HealthTemplate;
+
+async fn page() -> impl IntoResponse {
+    let template = HealthTemplate {};
+    HtmlTemplate(template)
+}
+
+#[derive(Deserialize)]
+struct HealthQuery {
+    window: Option<i32>,
+}
+
+#[derive(Debug, Clone)]
+struct LayerIssue {
+    layer: LayerId,
+    reason: &'static str,
+}
+
+#[derive(Debug, Clone)]
+struct WeakCoin {
+    layer: LayerId,
+    value: Option<bool>,
+}
+
+impl WeakCoin {
+    fn label(&self) -> &'static str {
+        match self.value {
+            Some(true) => "1",
+            Some(false) => "0",
+            None => "-",
+        }
+    }
+
+    fn color(&self) -> &'static str {
+        match self.value {
+            Some(true) => "bg-green-400",
+            Some(false) => "bg-neutral-400",
+            None => "bg-red-400",
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "components/health/status.html")]
+struct HealthStatusTemplate {
+    current_layer: u64,
+    last_layer: LayerId,
+    last_processed: LayerId,
+    last_certified: LayerId,
+    sync_lag: u64,
+    window: i32,
+    empty_layers: u64,
+    no_block_layers: u64,
+    missing_certificates: u64,
+    missing_hashes: u64,
+    repeated_hashes: u64,
+    unexplained_state_changes: u64,
+    gaps: u64,
+    issues: Vec<LayerIssue>,
+    weak_coins: Vec<WeakCoin>,
+}
+
+async fn status_handler(
+    State(state): State<AppState>,
+    Query(query): Query<HealthQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let conn = &state.database;
+    let window = query.window.unwrap_or(DEFAULT_WINDOW).clamp(1, MAX_WINDOW);
+
+    let global_state = current_global_state(&state).await;
+
+    let last_layer = layers::Entity::find()
+        .order_by_desc(layers::Column::Id)
+        .one(conn)
+        .await?
+        .map(|layer| layer.id)
+        .unwrap_or(0);
+
+    let last_processed = layers::Entity::find()
+        .filter(layers::Column::Processed.eq(1))
+        .order_by_desc(layers::Column::Id)
+        .one(conn)
+        .await?
+        .map(|layer| layer.id)
+        .unwrap_or(0);
+
+    let last_certified = certificates::Entity::find()
+        .filter(certificates::Column::Valid.eq(1))
+        .order_by_desc(certificates::Column::Layer)
+        .one(conn)
+        .await?
+        .map(|cert| cert.layer)
+        .unwrap_or(0);
+
+    let lowest = (last_layer - window + 1).max(0);
+
+    let db_layers = layers::Entity::find()
+        .filter(layers::Column::Id.gte(lowest))
+        .order_by_desc(layers::Column::Id)
+        .all(conn)
+        .await?;
+
+    let certified: HashSet<i32> = certificates::Entity::find()
+        .filter(certificates::Column::Layer.gte(lowest))
+        .filter(certificates::Column::Valid.eq(1))
+        .all(conn)
+        .await?
+        .iter()
+        .map(|cert| cert.layer)
+        .collect();
+
+    let by_id: HashMap<i32, &layers::Model> =
+        db_layers.iter().map(|layer| (layer.id, layer)).collect();
+
+    let mut issues = vec![];
+    let mut empty_layers = 0;
+    let mut repeated_hashes = 0;
+    let mut unexplained_state_changes = 0;
+    let mut no_block_layers = 0;
+    let mut missing_certificates = 0;
+    let mut missing_hashes = 0;
+
+    for layer in &db_layers {
+        let processed = layer.processed.unwrap_or(0) == 1;
+
+        let reason = match &layer.applied_block {
+            None if processed => {
+                no_block_layers += 1;
+                Some("no applied block")
+            }
+            Some(block) if block.iter().all(|byte| *byte == 0) => {
+                empty_layers += 1;
+                Some("empty applied block")
+            }
+            _ => None,
+        };
+
+        let reason = if processed && layer.aggregated_hash.is_none() {
+            missing_hashes += 1;
+            reason.or(Some("missing aggregated hash"))
+        } else {
+            reason
+        };
+
+        let reason = if processed && !certified.contains(&layer.id) {
+            missing_certificates += 1;
+            reason.or(Some("missing certificate"))
+        } else {
+            reason
+        };
+
+        // Each aggregated hash folds in the previous one, so consecutive
+        // processed layers never share it, and the state only changes when a
+        // non-empty block was applied.
+        let reason = match by_id.get(&(layer.id - 1)) {
+            Some(previous) => {
+                let repeated = processed
+                    && layer.aggregated_hash.is_some()
+                    && layer.aggregated_hash == previous.aggregated_hash;
+                let state_changed = layer.state_hash.is_some()
+                    && previous.state_hash.is_some()
+                    && layer.state_hash != previous.state_hash;
+                let has_block = layer
+                    .applied_block
+                    .as_ref()
+                    .is_some_and(|block| block.iter().any(|byte| *byte != 0));
+
+                if repeated {
+                    repeated_hashes += 1;
+                }
+                if state_changed && !has_block {
+                    unexplained_state_changes += 1;
+                }
+
+                repeated
+                    .then_some("aggregated hash repeats previous layer")
+                    .or((state_changed && !has_block)
+                        .then_some("state changed without applied block"))
+                    .or(reason)
+            }
+            None => reason,
+        };
+
+        if let Some(reason) = reason {
+            issues.push(LayerIssue {
+                layer: LayerId(layer.id),
+                reason,
+            });
+        }
+    }
+
+    let gaps = ((last_layer - lowest + 1).max(0) as u64).saturating_sub(db_layers.len() as u64);
+    issues.truncate(MAX_ISSUES);
+
+    let weak_coins = db_layers
+        .iter()
+        .take(WEAK_COIN_LAYERS)
+        .map(|layer| WeakCoin {
+            layer: LayerId(layer.id),
+            value: layer.weak_coin.map(|coin| coin == 1),
+        })
+        .collect();
+
+    let template = HealthStatusTemplate {
+        current_layer: global_state.current_layer,
+        last_layer: LayerId(last_layer),
+        last_processed: LayerId(last_processed),
+        last_certified: LayerId(last_certified),
+        sync_lag: global_state
+            .current_layer
+            .saturating_sub(last_processed as u64),
+        window,
+        empty_layers,
+        no_block_layers,
+        missing_certificates,
+        missing_hashes,
+        repeated_hashes,
+        unexplained_state_changes,
+        gaps,
+        issues,
+        weak_coins,
+    };
+    Ok(HtmlTemplate(template))
+}
@@ -0,0 +1,139 @@
+use crate::{
+    components::checkpoint::restore_layer,
+    db_entities::layers,
+    error::AppError,
+    pagination::{fetch_page, Cursor, PageQuery, Pagination},
+    view_models::Layer,
+    AppState, HtmlTemplate,
+};
+use askama::Template;
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use sea_orm::{ColumnTrait, Condition, EntityTrait, QueryFilter};
+use serde::{de, Deserialize, Deserializer};
+use std::{fmt, str::FromStr};
+
+/// Applied block id the mesh records for layers where hare agreed on no block.
+const EMPTY_BLOCK_ID: [u8; 20] = [0; 20];
+
+pub fn layers_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(page))
+        .route("/list", get(list_handler))
+}
+
+#[derive(Template)]
+#[template(path = "pages/layers.html")]
+struct LayersTemplate;
+
+async fn page() -> impl IntoResponse {
+    let template = LayersTemplate {};
+    HtmlTemplate(template)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum LayerFilter {
+    #[default]
+    All,
+    Processed,
+    Unprocessed,
+    Empty,
+    NoBlock,
+}
+
+impl LayerFilter {
+    fn name(&self) -> &'static str {
+        match self {
+            LayerFilter::All => "all",
+            LayerFilter::Processed => "processed",
+            LayerFilter::Unprocessed => "unprocessed",
+            LayerFilter::Empty => "empty",
+            LayerFilter::NoBlock => "no_block",
+        }
+    }
+
+    fn condition(&self) -> Condition {
+        match self {
+            LayerFilter::All => Condition::all(),
+            LayerFilter::Processed => Condition::all().add(layers::Column::Processed.eq(1)),
+            LayerFilter::Unprocessed => Condition::any()
+                .add(layers::Column::Processed.eq(0))
+                .add(layers::Column::Processed.is_null()),
+            LayerFilter::Empty => {
+                Condition::all().add(layers::Column::AppliedBlock.eq(EMPTY_BLOCK_ID.to_vec()))
+            }
+            LayerFilter::NoBlock => Condition::all().add(layers::Column::AppliedBlock.is_null()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LayersQuery {
+    filter: Option<LayerFilter>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    from: Option<i32>,
+}
+
+/// The filter form always submits the jump box, so an empty `from` means no
+/// jump rather than a malformed number.
+fn empty_as_none<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    match Option::<String>::deserialize(deserializer)?
+        .as_deref()
+        .map(str::trim)
+    {
+        None | Some("") => Ok(None),
+        Some(value) => value.parse().map(Some).map_err(de::Error::custom),
+    }
+}
+
+#[derive(Template)]
+#[template(path = "components/layers/layers_list.html")]
+struct LayersListTemplate {
+    layers: Vec<Layer>,
+    pagination: Pagination,
+}
+
+async fn list_handler(
+    State(state): State<AppState>,
+    Query(mut query): Query<PageQuery>,
+    Query(layers_query): Query<LayersQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let conn = &state.database;
+    let filter = layers_query.filter.unwrap_or_default();
+
+    if let Some(from) = layers_query.from {
+        if query.after.is_none() && query.before.is_none() {
+            query.after = Some(Cursor::new(from.saturating_add(1), &[]).encode());
+        }
+    }
+
+    let page = fetch_page(
+        conn,
+        layers::Entity::find().filter(filter.condition()),
+        layers::Column::Id,
+        None,
+        &query,
+        |layer| Cursor::new(layer.id, &[]),
+    )
+    .await?
+    .map(Layer::from);
+
+    let pagination = Pagination::new("/layers/list".to_string(), "layers-list", &page)
+        .with_params(format!("filter={}&", filter.name()))
+        .with_checkpoint(restore_layer(conn).await?);
+    let template = LayersListTemplate {
+        layers: page.items,
+        pagination,
+    };
+    Ok(HtmlTemplate(template))
+}
@@ -0,0 +1,70 @@
+use crate::{
+    db_entities::identities, error::AppError, types::NodeId, view_models::MaliciousIdentity,
+    vm::Offense, AppState, HtmlTemplate,
+};
+use askama::Template;
+use axum::{extract::State, response::IntoResponse, routing::get, Router};
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder};
+use std::collections::BTreeMap;
+
+pub fn malfeasance_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(page))
+        .route("/list", get(list_handler))
+}
+
+/// Malfeasance proof held against `smesher`, if any.
+pub async fn find_malfeasance(
+    conn: &DatabaseConnection,
+    smesher: &NodeId,
+) -> Result<Option<MaliciousIdentity>, DbErr> {
+    Ok(identities::Entity::find()
+        .filter(identities::Column::Pubkey.eq(smesher.to_string()))
+        .filter(identities::Column::Proof.is_not_null())
+        .one(conn)
+        .await?
+        .map(MaliciousIdentity::from))
+}
+
+#[derive(Template)]
+#[template(path = "pages/malfeasance.html")]
+struct MalfeasanceTemplate;
+
+async fn page() -> impl IntoResponse {
+    let template = MalfeasanceTemplate {};
+    HtmlTemplate(template)
+}
+
+#[derive(Template)]
+#[template(path = "components/malfeasance/malfeasance_list.html")]
+struct MalfeasanceListTemplate {
+    identities: Vec<MaliciousIdentity>,
+    offenses: Vec<(&'static str, usize)>,
+}
+
+async fn list_handler(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let identities: Vec<MaliciousIdentity> = identities::Entity::find()
+        .filter(identities::Column::Proof.is_not_null())
+        .order_by_desc(identities::Column::Received)
+        .all(&state.database)
+        .await?
+        .into_iter()
+        .map(MaliciousIdentity::from)
+        .collect();
+
+    let mut offenses = BTreeMap::new();
+    for identity in &identities {
+        let name = identity
+            .proof
+            .as_ref()
+            .map(|proof| proof.offense.name())
+            .unwrap_or("Unknown");
+        *offenses.entry(name).or_insert(0) += 1;
+    }
+
+    let template = MalfeasanceListTemplate {
+        identities,
+        offenses: offenses.into_iter().collect(),
+    };
+    Ok(HtmlTemplate(template))
+}
@@ -0,0 +1,124 @@
+use crate::{
+    db_entities::transactions,
+    error::{ApiError, AppError},
+    pagination::{fetch_page, Cursor, Page, PageQuery, Pagination, NULL_KEY},
+    types::Address,
+    view_models::PendingTx,
+    AppState, HtmlTemplate,
+};
+use askama::Template;
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use chrono::Utc;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Select};
+use serde::Deserialize;
+
+pub fn mempool_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(page))
+        .route("/list", get(list_handler))
+}
+
+pub fn mempool_api_routes() -> Router<AppState> {
+    Router::new().route("/", get(api_handler))
+}
+
+/// Transactions without a layer: received by the node but not applied yet.
+pub fn pending() -> Select<transactions::Entity> {
+    transactions::Entity::find().filter(transactions::Column::Layer.is_null())
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MempoolQuery {
+    principal: Option<String>,
+}
+
+impl MempoolQuery {
+    fn principal(&self) -> Result<Option<Address>, AppError> {
+        match self.principal.as_deref().map(str::trim) {
+            None | Some("") => Ok(None),
+            Some(principal) => Ok(Some(principal.parse()?)),
+        }
+    }
+}
+
+async fn pending_page(
+    conn: &DatabaseConnection,
+    query: &PageQuery,
+    principal: Option<Address>,
+) -> Result<Page<PendingTx>, AppError> {
+    let mut select = pending();
+    if let Some(principal) = principal {
+        select = select.filter(transactions::Column::Principal.eq(principal));
+    }
+
+    let now = Utc::now().naive_utc();
+    let page = fetch_page(
+        conn,
+        select,
+        transactions::Column::Layer,
+        Some(transactions::Column::Id),
+        query,
+        |tx| Cursor::new(tx.layer.unwrap_or(NULL_KEY), &tx.id),
+    )
+    .await?
+    .map(|tx| PendingTx::new(tx, now));
+
+    Ok(page)
+}
+
+#[derive(Template)]
+#[template(path = "pages/mempool.html")]
+struct MempoolTemplate {
+    principal: String,
+}
+
+async fn page(Query(mempool_query): Query<MempoolQuery>) -> impl IntoResponse {
+    let template = MempoolTemplate {
+        principal: mempool_query.principal.unwrap_or_default(),
+    };
+    HtmlTemplate(template)
+}
+
+#[derive(Template)]
+#[template(path = "components/mempool/mempool_list.html")]
+struct MempoolListTemplate {
+    txs: Vec<PendingTx>,
+    pagination: Pagination,
+}
+
+async fn list_handler(
+    State(state): State<AppState>,
+    Query(query): Query<PageQuery>,
+    Query(mempool_query): Query<MempoolQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let principal = mempool_query.principal()?;
+    let params = principal
+        .as_ref()
+        .map(|principal| format!("principal={}&", principal))
+        .unwrap_or_default();
+
+    let page = pending_page(&state.database, &query, principal).await?;
+
+    let pagination =
+        Pagination::new("/mempool/list".to_string(), "mempool-list", &page).with_params(params);
+    let template = MempoolListTemplate {
+        txs: page.items,
+        pagination,
+    };
+    Ok(HtmlTemplate(template))
+}
+
+async fn api_handler(
+    State(state): State<AppState>,
+    Query(query): Query<PageQuery>,
+    Query(mempool_query): Query<MempoolQuery>,
+) -> Result<Json<Page<PendingTx>>, ApiError> {
+    let principal = mempool_query.principal()?;
+    let page = pending_page(&state.database, &query, principal).await?;
+    Ok(Json(page))
+}
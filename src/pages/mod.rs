@@ -1,3 +1,15 @@
 pub mod account;
+pub mod analytics;
+pub mod atx;
+pub mod beacons;
+pub mod epoch;
+pub mod health;
 pub mod home;
 pub mod layer;
+pub mod layers;
+pub mod malfeasance;
+pub mod mempool;
+pub mod poets;
+pub mod smesher;
+pub mod stats;
+pub mod transaction;
@@ -0,0 +1,114 @@
+use crate::{
+    db_entities::poets, error::AppError, types::PoetRef, view_models::PoetRound, AppState,
+    HtmlTemplate,
+};
+use askama::Template;
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait};
+use std::{cmp::Reverse, collections::BTreeMap};
+
+pub fn poets_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(page))
+        .route("/list", get(list_handler))
+        .route("/:ref", get(round_page))
+        .route("/:ref/round", get(round_handler))
+}
+
+/// Round of the proof `proof_ref`, if the node stored it.
+pub async fn find_round(
+    conn: &DatabaseConnection,
+    proof_ref: &PoetRef,
+) -> Result<Option<PoetRound>, DbErr> {
+    Ok(poets::Entity::find_by_id(proof_ref.to_string())
+        .one(conn)
+        .await?
+        .map(PoetRound::from))
+}
+
+/// Rounds a PoET service ran, as far as this node knows.
+struct PoetService {
+    id: String,
+    rounds: usize,
+    latest: String,
+}
+
+#[derive(Template)]
+#[template(path = "pages/poets.html")]
+struct PoetsTemplate;
+
+async fn page() -> impl IntoResponse {
+    let template = PoetsTemplate {};
+    HtmlTemplate(template)
+}
+
+#[derive(Template)]
+#[template(path = "components/poets/poets_list.html")]
+struct PoetsListTemplate {
+    services: Vec<PoetService>,
+    rounds: Vec<PoetRound>,
+}
+
+async fn list_handler(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let mut rounds: Vec<PoetRound> = poets::Entity::find()
+        .all(&state.database)
+        .await?
+        .into_iter()
+        .map(PoetRound::from)
+        .collect();
+    rounds.sort_by(|a, b| {
+        a.service_id
+            .cmp(&b.service_id)
+            .then_with(|| Reverse(a.round_number()).cmp(&Reverse(b.round_number())))
+    });
+
+    let mut services: BTreeMap<&str, PoetService> = BTreeMap::new();
+    for round in &rounds {
+        let service = services
+            .entry(&round.service_id)
+            .or_insert_with(|| PoetService {
+                id: round.service_id.clone(),
+                rounds: 0,
+                latest: round.round_id.clone(),
+            });
+        service.rounds += 1;
+    }
+    let services = services.into_values().collect();
+
+    let template = PoetsListTemplate { services, rounds };
+    Ok(HtmlTemplate(template))
+}
+
+#[derive(Template)]
+#[template(path = "pages/poet.html")]
+struct PoetTemplate {
+    id: String,
+}
+
+async fn round_page(Path(id): Path<String>) -> impl IntoResponse {
+    let template = PoetTemplate { id };
+    HtmlTemplate(template)
+}
+
+#[derive(Template)]
+#[template(path = "components/poets/round_tab.html")]
+struct RoundTabTemplate {
+    round: PoetRound,
+}
+
+async fn round_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let round = find_round(&state.database, &id.parse::<PoetRef>()?)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("PoET proof {} not found", id)))?;
+
+    let template = RoundTabTemplate { round };
+    Ok(HtmlTemplate(template))
+}
@@ -0,0 +1,103 @@
+use crate::{
+    components::checkpoint::restore_layer,
+    db_entities::atxs,
+    error::AppError,
+    pages::malfeasance::find_malfeasance,
+    pagination::{fetch_page, Cursor, PageQuery, Pagination},
+    types::NodeId,
+    view_models::{Atx, MaliciousIdentity},
+    vm::Offense,
+    AppState, HtmlTemplate,
+};
+use askama::Template;
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+
+pub fn smesher_routes() -> Router<AppState> {
+    Router::new()
+        .route("/:id", get(page))
+        .route("/:id/smesher", get(smesher_handler))
+        .route("/:id/atxs", get(atxs_handler))
+}
+
+#[derive(Template)]
+#[template(path = "pages/smesher.html")]
+struct SmesherTemplate {
+    id: String,
+}
+
+async fn page(Path(id): Path<String>) -> impl IntoResponse {
+    let template = SmesherTemplate { id };
+    HtmlTemplate(template)
+}
+
+#[derive(Template)]
+#[template(path = "components/smesher/smesher_tab.html")]
+struct SmesherTabTemplate {
+    smesher: NodeId,
+    atxs: u64,
+    malicious: Option<MaliciousIdentity>,
+}
+
+async fn smesher_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let conn = &state.database;
+    let smesher = id.parse::<NodeId>()?;
+
+    let atxs = atxs::Entity::find()
+        .filter(atxs::Column::Pubkey.eq(smesher.clone()))
+        .count(conn)
+        .await?;
+    let malicious = find_malfeasance(conn, &smesher).await?;
+    if atxs == 0 && malicious.is_none() {
+        return Err(AppError::NotFound(format!("Smesher {} not found", id)));
+    }
+
+    let template = SmesherTabTemplate {
+        smesher,
+        atxs,
+        malicious,
+    };
+    Ok(HtmlTemplate(template))
+}
+
+#[derive(Template)]
+#[template(path = "components/smesher/atxs_tab.html")]
+struct AtxsTabTemplate {
+    atxs: Vec<Atx>,
+    pagination: Pagination,
+}
+
+async fn atxs_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let conn = &state.database;
+
+    let page = fetch_page(
+        conn,
+        atxs::Entity::find().filter(atxs::Column::Pubkey.eq(id.parse::<NodeId>()?)),
+        atxs::Column::Epoch,
+        Some(atxs::Column::Id),
+        &query,
+        |atx| Cursor::new(atx.epoch, &atx.id),
+    )
+    .await?
+    .map(Atx::from);
+
+    let pagination = Pagination::new(format!("/smesher/{}/atxs", id), "smesher-atxs", &page)
+        .with_checkpoint(restore_layer(conn).await?);
+    let template = AtxsTabTemplate {
+        atxs: page.items,
+        pagination,
+    };
+    Ok(HtmlTemplate(template))
+}
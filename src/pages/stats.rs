@@ -0,0 +1,346 @@
+use crate::{
+    db_entities::transactions,
+    error::{ApiError, AppError},
+    types::Amount,
+    view_models::{Activity, Chart},
+    vm::{Call, RawTx, TxResult, TxStatus},
+    AppState, HtmlTemplate,
+};
+use askama::Template;
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    sync::Mutex,
+};
+
+pub const DEFAULT_PERIODS: u32 = 30;
+pub const MAX_PERIODS: u32 = 50;
+const NANOS_PER_DAY: i64 = 86_400 * 1_000_000_000;
+
+pub fn stats_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(page))
+        .route("/charts", get(charts_handler))
+}
+
+pub fn stats_api_routes() -> Router<AppState> {
+    Router::new().route("/", get(api_handler))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Layer,
+    #[default]
+    Epoch,
+    Day,
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Period::Layer => "layer",
+            Period::Epoch => "epoch",
+            Period::Day => "day",
+        })
+    }
+}
+
+/// Query string of the stats page and API: the last `count` periods.
+#[derive(Debug, Default, Deserialize)]
+struct StatsQuery {
+    #[serde(default)]
+    period: Period,
+    count: Option<u32>,
+}
+
+impl StatsQuery {
+    fn count(&self) -> i64 {
+        self.count
+            .unwrap_or(DEFAULT_PERIODS)
+            .clamp(1, MAX_PERIODS)
+            .into()
+    }
+}
+
+#[derive(Serialize)]
+struct Stats {
+    period: Period,
+    activity: Vec<Activity>,
+}
+
+#[derive(Default)]
+struct Totals {
+    transactions: u64,
+    accounts: HashSet<Vec<u8>>,
+    transferred: u64,
+    fees: u64,
+    gas: u64,
+}
+
+impl Totals {
+    fn add(&mut self, tx: transactions::Model) {
+        self.transactions += 1;
+        if let Some(principal) = tx.principal {
+            self.accounts.insert(principal);
+        }
+        let Some(result) = tx.result.as_deref().and_then(TxResult::decode) else {
+            return;
+        };
+        self.fees = self.fees.saturating_add(result.fee.smidge());
+        self.gas = self.gas.saturating_add(result.gas);
+        if result.status != TxStatus::Success {
+            return;
+        }
+        let amount = match tx.tx.as_deref().and_then(RawTx::decode).map(|raw| raw.call) {
+            Some(Call::Spend { amount, .. } | Call::DrainVault { amount, .. }) => amount.smidge(),
+            _ => 0,
+        };
+        self.transferred = self.transferred.saturating_add(amount);
+    }
+
+    fn into_activity(self, period: String) -> Activity {
+        Activity {
+            period,
+            transactions: self.transactions,
+            accounts: self.accounts.len() as u64,
+            transferred: Amount(self.transferred),
+            fees: Amount(self.fees),
+            gas: self.gas,
+        }
+    }
+}
+
+/// Activity of finished periods, which new transactions no longer change,
+/// so that only the latest ones are read again.
+#[derive(Default)]
+pub struct ActivityCache {
+    periods: Mutex<HashMap<Period, BTreeMap<i64, Activity>>>,
+}
+
+impl ActivityCache {
+    fn get(&self, period: Period, keys: std::ops::RangeInclusive<i64>) -> BTreeMap<i64, Activity> {
+        let periods = self
+            .periods
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        periods
+            .get(&period)
+            .map(|cached| {
+                cached
+                    .range(keys)
+                    .map(|(key, activity)| (*key, activity.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Keeps the periods before `finished` among `activity`, and forgets
+    /// those too old to be asked for.
+    fn insert(&self, period: Period, activity: &BTreeMap<i64, Activity>, finished: i64) {
+        let mut periods = self
+            .periods
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let cached = periods.entry(period).or_default();
+        cached.extend(
+            activity
+                .range(..finished)
+                .map(|(key, activity)| (*key, activity.clone())),
+        );
+        *cached = cached.split_off(&(finished - i64::from(MAX_PERIODS)));
+    }
+}
+
+/// Activity of the last `count` periods up to the newest executed
+/// transaction, oldest first, with quiet periods included.
+async fn activity(
+    conn: &DatabaseConnection,
+    cache: &ActivityCache,
+    period: Period,
+    count: i64,
+    epoch_num_layers: i64,
+) -> Result<Vec<Activity>, AppError> {
+    if period == Period::Epoch && epoch_num_layers == 0 {
+        return Err(AppError::BadRequest(
+            "Epoch length is not known yet".to_string(),
+        ));
+    }
+
+    let executed =
+        || transactions::Entity::find().filter(transactions::Column::Layer.is_not_null());
+    let latest = executed()
+        .select_only()
+        .column_as(transactions::Column::Layer.max(), "layer")
+        .column_as(transactions::Column::Timestamp.max(), "timestamp")
+        .into_tuple::<(Option<i32>, Option<i64>)>()
+        .one(conn)
+        .await?;
+    let Some((Some(layer), Some(timestamp))) = latest else {
+        return Ok(vec![]);
+    };
+
+    let last = match period {
+        Period::Layer => i64::from(layer),
+        Period::Epoch => i64::from(layer) / epoch_num_layers,
+        Period::Day => timestamp.div_euclid(NANOS_PER_DAY),
+    };
+    let first = (last - count + 1).max(0);
+    // Transactions of the latest layer may still be executed, and those of
+    // the day before may still be received late.
+    let finished = match period {
+        Period::Layer | Period::Epoch => last,
+        Period::Day => last - 1,
+    };
+
+    let mut activity = cache.get(period, first..=last);
+    let missing = (first..=last)
+        .find(|key| !activity.contains_key(key))
+        .unwrap_or(last + 1);
+
+    if missing <= last {
+        let query = match period {
+            Period::Layer => executed().filter(transactions::Column::Layer.gte(missing)),
+            Period::Epoch => {
+                executed().filter(transactions::Column::Layer.gte(missing * epoch_num_layers))
+            }
+            Period::Day => {
+                executed().filter(transactions::Column::Timestamp.gte(missing * NANOS_PER_DAY))
+            }
+        };
+
+        let mut totals: BTreeMap<i64, Totals> = BTreeMap::new();
+        for tx in query.all(conn).await? {
+            let key = match period {
+                Period::Layer => tx.layer.map(i64::from),
+                Period::Epoch => tx.layer.map(|layer| i64::from(layer) / epoch_num_layers),
+                Period::Day => Some(tx.timestamp.div_euclid(NANOS_PER_DAY)),
+            };
+            if let Some(key) = key.filter(|key| (missing..=last).contains(key)) {
+                totals.entry(key).or_default().add(tx);
+            }
+        }
+
+        for key in missing..=last {
+            let label = match period {
+                Period::Layer | Period::Epoch => key.to_string(),
+                Period::Day => NaiveDateTime::from_timestamp_opt(key * 86_400, 0)
+                    .map(|day| day.date().to_string())
+                    .unwrap_or_default(),
+            };
+            let totals = totals.remove(&key).unwrap_or_default();
+            activity.insert(key, totals.into_activity(label));
+        }
+        cache.insert(period, &activity, finished);
+    }
+
+    Ok(activity.into_values().collect())
+}
+
+#[derive(Template)]
+#[template(path = "pages/stats.html")]
+struct StatsTemplate {
+    period: Period,
+}
+
+async fn page(Query(query): Query<StatsQuery>) -> impl IntoResponse {
+    let template = StatsTemplate {
+        period: query.period,
+    };
+    HtmlTemplate(template)
+}
+
+#[derive(Template)]
+#[template(path = "components/stats/charts.html")]
+struct ChartsTemplate {
+    charts: Vec<Chart>,
+}
+
+async fn charts_handler(
+    State(state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let epoch_num_layers = state.global_state.read().await.epoch_num_layers as i64;
+    let activity = activity(
+        &state.database,
+        &state.activity,
+        query.period,
+        query.count(),
+        epoch_num_layers,
+    )
+    .await?;
+
+    let label = |activity: &Activity| match query.period {
+        Period::Layer => format!("Layer {}", activity.period),
+        Period::Epoch => format!("Epoch {}", activity.period),
+        Period::Day => activity.period.clone(),
+    };
+    let chart = |title: &str, value: fn(&Activity) -> f64, shown: fn(&Activity) -> String| {
+        Chart::new(
+            title,
+            activity
+                .iter()
+                .map(|activity| (label(activity), value(activity), shown(activity))),
+        )
+    };
+
+    let template = ChartsTemplate {
+        charts: vec![
+            chart(
+                "Transactions",
+                |activity| activity.transactions as f64,
+                |activity| activity.transactions.to_string(),
+            ),
+            chart(
+                "Active accounts",
+                |activity| activity.accounts as f64,
+                |activity| activity.accounts.to_string(),
+            ),
+            chart(
+                "Transferred",
+                |activity| activity.transferred.smidge() as f64,
+                |activity| format!("{} SMH", activity.transferred),
+            ),
+            chart(
+                "Fees",
+                |activity| activity.fees.smidge() as f64,
+                |activity| format!("{} SMH", activity.fees),
+            ),
+            chart(
+                "Gas used",
+                |activity| activity.gas as f64,
+                |activity| activity.gas.to_string(),
+            ),
+        ],
+    };
+    Ok(HtmlTemplate(template))
+}
+
+async fn api_handler(
+    State(state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<impl Serialize>, ApiError> {
+    let epoch_num_layers = state.global_state.read().await.epoch_num_layers as i64;
+    let activity = activity(
+        &state.database,
+        &state.activity,
+        query.period,
+        query.count(),
+        epoch_num_layers,
+    )
+    .await?;
+
+    Ok(Json(Stats {
+        period: query.period,
+        activity,
+    }))
+}
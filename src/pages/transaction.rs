@@ -0,0 +1,128 @@
+use crate::{
+    db_entities::{block_transactions, layers, proposal_transactions, transactions},
+    error::AppError,
+    types::{BlockId, LayerId, ProposalId, TxId},
+    view_models::{BlockInclusion, ProposalInclusion, Timeline, TransactionDetails},
+    vm::{Call, TxResult},
+    AppState, HtmlTemplate,
+};
+use askama::Template;
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use chrono::NaiveDateTime;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use std::collections::HashMap;
+
+pub fn transaction_routes() -> Router<AppState> {
+    Router::new()
+        .route("/:id", get(page))
+        .route("/:id/transaction", get(transaction_handler))
+        .route("/:id/timeline", get(timeline_handler))
+}
+
+#[derive(Template)]
+#[template(path = "pages/transaction.html")]
+struct TransactionPageTemplate {
+    id: String,
+}
+
+async fn page(Path(id): Path<String>) -> impl IntoResponse {
+    let template = TransactionPageTemplate { id };
+    HtmlTemplate(template)
+}
+
+async fn find_transaction(
+    conn: &DatabaseConnection,
+    id: &str,
+) -> Result<transactions::Model, AppError> {
+    transactions::Entity::find()
+        .filter(transactions::Column::Id.eq(id.parse::<TxId>()?))
+        .one(conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Transaction {} not found", id)))
+}
+
+#[derive(Template)]
+#[template(path = "components/transaction/transaction_tab.html")]
+struct TransactionTabTemplate {
+    details: TransactionDetails,
+}
+
+async fn transaction_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let tx = find_transaction(&state.database, &id).await?;
+
+    let template = TransactionTabTemplate {
+        details: TransactionDetails::from(tx),
+    };
+    Ok(HtmlTemplate(template))
+}
+
+#[derive(Template)]
+#[template(path = "components/transaction/timeline_tab.html")]
+struct TimelineTabTemplate {
+    timeline: Timeline,
+}
+
+async fn timeline_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let conn = &state.database;
+    let tx = find_transaction(conn, &id).await?;
+
+    let proposals = proposal_transactions::Entity::find()
+        .filter(proposal_transactions::Column::Tid.eq(tx.id.clone()))
+        .order_by_asc(proposal_transactions::Column::Layer)
+        .order_by_asc(proposal_transactions::Column::Pid)
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|proposal| ProposalInclusion {
+            proposal: ProposalId(proposal.pid),
+            layer: LayerId(proposal.layer),
+        })
+        .collect();
+
+    let included = block_transactions::Entity::find()
+        .filter(block_transactions::Column::Tid.eq(tx.id.clone()))
+        .order_by_asc(block_transactions::Column::Layer)
+        .order_by_asc(block_transactions::Column::Bid)
+        .all(conn)
+        .await?;
+
+    let applied: HashMap<i32, Vec<u8>> = layers::Entity::find()
+        .filter(layers::Column::Id.is_in(included.iter().map(|block| block.layer)))
+        .all(conn)
+        .await?
+        .into_iter()
+        .filter_map(|layer| Some((layer.id, layer.applied_block?)))
+        .collect();
+
+    let blocks = included
+        .into_iter()
+        .map(|block| BlockInclusion {
+            applied: applied.get(&block.layer) == Some(&block.bid),
+            block: BlockId(block.bid),
+            layer: LayerId(block.layer),
+        })
+        .collect();
+
+    let timeline = Timeline {
+        received: NaiveDateTime::from_timestamp_millis(tx.timestamp / 1_000_000)
+            .map(|received| received.to_string())
+            .unwrap_or_default(),
+        proposals,
+        blocks,
+        result: tx.result.as_deref().and_then(TxResult::decode),
+    };
+
+    let template = TimelineTabTemplate { timeline };
+    Ok(HtmlTemplate(template))
+}
@@ -0,0 +1,343 @@
+use crate::types::LayerId;
+use sea_orm::{
+    sea_query::{Expr, Func, SimpleExpr},
+    ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, FromQueryResult, Order,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Select,
+};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_PAGE_SIZE: u64 = 20;
+pub const MAX_PAGE_SIZE: u64 = 100;
+pub const PAGE_SIZES: [u64; 4] = [10, 20, 50, 100];
+
+/// Query string accepted by every paginated list.
+///
+/// `after` walks towards older rows, `before` towards newer ones. Both are
+/// cursors produced by [`Cursor::encode`].
+#[derive(Debug, Default, Deserialize)]
+pub struct PageQuery {
+    pub after: Option<String>,
+    pub before: Option<String>,
+    pub limit: Option<u64>,
+}
+
+impl PageQuery {
+    pub fn limit(&self) -> u64 {
+        self.limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE)
+    }
+}
+
+/// Position in a list ordered by `(key, id)` descending, where `key` is a
+/// layer or epoch number and `id` the row's binary id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub key: i32,
+    pub id: Vec<u8>,
+}
+
+impl Cursor {
+    pub fn new(key: i32, id: &[u8]) -> Self {
+        Cursor {
+            key,
+            id: id.to_vec(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        if self.id.is_empty() {
+            return self.key.to_string();
+        }
+        format!("{}-{}", self.key, hex::encode(&self.id))
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let (key, id) = cursor.split_once('-').unwrap_or((cursor, ""));
+        Some(Cursor {
+            key: key.parse().ok()?,
+            id: hex::decode(id).ok()?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Page<M> {
+    pub items: Vec<M>,
+    pub total: u64,
+    pub limit: u64,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
+impl<M> Page<M> {
+    /// Converts every item, keeping the cursors and counts.
+    pub fn map<T>(self, f: impl FnMut(M) -> T) -> Page<T> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            limit: self.limit,
+            next: self.next,
+            prev: self.prev,
+        }
+    }
+}
+
+/// Cursor key used for rows whose key column is NULL, such as transactions
+/// that are not in a layer yet. Those rows sort before every real key.
+pub const NULL_KEY: i32 = i32::MAX;
+
+/// The ordering expression for `key`: nullable columns are coalesced to
+/// [`NULL_KEY`] so the keyset conditions below can reach NULL rows.
+fn key_expr<C: ColumnTrait>(key: C) -> SimpleExpr {
+    if key.def().is_null() {
+        Func::coalesce([
+            SimpleExpr::from(key.into_expr()),
+            Expr::val(NULL_KEY).into(),
+        ])
+        .into()
+    } else {
+        SimpleExpr::from(key.into_expr())
+    }
+}
+
+/// Fetches one page of `select` ordered by `(key, id)` descending.
+///
+/// Rows are located with a keyset condition instead of an offset, so deep
+/// pages cost the same as the first one. Tables whose `key` is unique on its
+/// own pass `None` for `id`.
+pub async fn fetch_page<E, C>(
+    conn: &DatabaseConnection,
+    select: Select<E>,
+    key: C,
+    id: Option<C>,
+    query: &PageQuery,
+    cursor_of: impl Fn(&E::Model) -> Cursor,
+) -> Result<Page<E::Model>, DbErr>
+where
+    E: EntityTrait<Column = C>,
+    E::Model: FromQueryResult + Sized + Send + Sync,
+    C: ColumnTrait,
+{
+    let limit = query.limit();
+    let total = select.clone().count(conn).await?;
+    let key = key_expr(key);
+
+    let before = query.before.as_deref().and_then(Cursor::decode);
+    let after = query.after.as_deref().and_then(Cursor::decode);
+
+    let page = if let Some(cursor) = before {
+        let mut condition = Condition::any().add(Expr::expr(key.clone()).gt(cursor.key));
+        let mut ordered = select.order_by(key.clone(), Order::Asc);
+        if let Some(id) = id {
+            condition = condition.add(
+                Condition::all()
+                    .add(Expr::expr(key).eq(cursor.key))
+                    .add(id.gt(cursor.id)),
+            );
+            ordered = ordered.order_by_asc(id);
+        }
+
+        let mut items = ordered.filter(condition).limit(limit + 1).all(conn).await?;
+
+        let has_more = items.len() as u64 > limit;
+        items.truncate(limit as usize);
+        items.reverse();
+
+        Page {
+            prev: has_more
+                .then(|| items.first().map(|m| cursor_of(m).encode()))
+                .flatten(),
+            next: items.last().map(|m| cursor_of(m).encode()),
+            items,
+            total,
+            limit,
+        }
+    } else {
+        let mut ordered = select.order_by(key.clone(), Order::Desc);
+        if let Some(id) = id {
+            ordered = ordered.order_by_desc(id);
+        }
+        if let Some(cursor) = &after {
+            let mut condition = Condition::any().add(Expr::expr(key.clone()).lt(cursor.key));
+            if let Some(id) = id {
+                condition = condition.add(
+                    Condition::all()
+                        .add(Expr::expr(key).eq(cursor.key))
+                        .add(id.lt(cursor.id.clone())),
+                );
+            }
+            ordered = ordered.filter(condition);
+        }
+
+        let mut items = ordered.limit(limit + 1).all(conn).await?;
+
+        let has_more = items.len() as u64 > limit;
+        items.truncate(limit as usize);
+
+        Page {
+            prev: after
+                .is_some()
+                .then(|| items.first().map(|m| cursor_of(m).encode()))
+                .flatten(),
+            next: has_more
+                .then(|| items.last().map(|m| cursor_of(m).encode()))
+                .flatten(),
+            items,
+            total,
+            limit,
+        }
+    };
+
+    Ok(page)
+}
+
+/// Navigation state rendered by `components/pagination.html`.
+///
+/// `url` is the fragment endpoint without a query string, `params` any extra
+/// query parameters (ending in `&`) the list needs to keep, and `target` the
+/// id of the element the fragment replaces.
+#[derive(Debug, Clone)]
+pub struct Pagination {
+    pub url: String,
+    pub params: String,
+    pub target: String,
+    pub total: u64,
+    pub limit: u64,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+    /// Restore layer of the node, explaining why the last page ends early.
+    pub checkpoint: Option<LayerId>,
+}
+
+impl Pagination {
+    pub fn new<M>(url: String, target: &str, page: &Page<M>) -> Self {
+        Pagination {
+            url,
+            params: String::new(),
+            target: target.to_string(),
+            total: page.total,
+            limit: page.limit,
+            next: page.next.clone(),
+            prev: page.prev.clone(),
+            checkpoint: None,
+        }
+    }
+
+    pub fn with_checkpoint(mut self, checkpoint: Option<LayerId>) -> Self {
+        self.checkpoint = checkpoint;
+        self
+    }
+
+    pub fn with_params(mut self, params: String) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn sizes(&self) -> [u64; 4] {
+        PAGE_SIZES
+    }
+
+    pub fn is_current(&self, size: &u64) -> bool {
+        *size == self.limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db_entities::transactions, tests::fixture};
+
+    fn query(after: Option<&str>, before: Option<&str>, limit: u64) -> PageQuery {
+        PageQuery {
+            after: after.map(str::to_string),
+            before: before.map(str::to_string),
+            limit: Some(limit),
+        }
+    }
+
+    async fn transactions_page(
+        conn: &DatabaseConnection,
+        query: &PageQuery,
+    ) -> Page<transactions::Model> {
+        fetch_page(
+            conn,
+            transactions::Entity::find(),
+            transactions::Column::Layer,
+            Some(transactions::Column::Id),
+            query,
+            |tx| Cursor::new(tx.layer.unwrap_or(NULL_KEY), &tx.id),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn cursor_round_trips() {
+        for cursor in [
+            Cursor::new(12, &[0xab, 0x01]),
+            Cursor::new(7, &[]),
+            Cursor::new(NULL_KEY, &[0xff; 32]),
+        ] {
+            assert_eq!(Cursor::decode(&cursor.encode()), Some(cursor));
+        }
+
+        assert_eq!(Cursor::new(20, &[]).encode(), "20");
+        assert_eq!(Cursor::decode("nonsense"), None);
+        assert_eq!(Cursor::decode("3-zz"), None);
+    }
+
+    #[test]
+    fn limit_is_clamped() {
+        assert_eq!(PageQuery::default().limit(), DEFAULT_PAGE_SIZE);
+        assert_eq!(query(None, None, 0).limit(), 1);
+        assert_eq!(query(None, None, 10_000).limit(), MAX_PAGE_SIZE);
+    }
+
+    #[tokio::test]
+    async fn walks_after_and_back_before() {
+        let conn = fixture().await;
+        let total = transactions::Entity::find().count(&conn).await.unwrap();
+
+        let mut forward = vec![];
+        let mut page = transactions_page(&conn, &query(None, None, 4)).await;
+        assert_eq!(page.prev, None);
+        loop {
+            assert_eq!(page.total, total);
+            forward.extend(page.items.iter().map(|tx| tx.id.clone()));
+            match page.next.clone() {
+                Some(next) => page = transactions_page(&conn, &query(Some(&next), None, 4)).await,
+                None => break,
+            }
+        }
+        assert_eq!(forward.len() as u64, total);
+        assert!(forward[0].iter().all(|byte| *byte >= 0xf0), "pending first");
+
+        let mut backward = vec![];
+        loop {
+            backward.splice(0..0, page.items.iter().map(|tx| tx.id.clone()));
+            match page.prev.clone() {
+                Some(prev) => page = transactions_page(&conn, &query(None, Some(&prev), 4)).await,
+                None => break,
+            }
+        }
+        assert_eq!(backward, forward);
+    }
+
+    #[tokio::test]
+    async fn last_page_has_no_next() {
+        let conn = fixture().await;
+        let total = transactions::Entity::find().count(&conn).await.unwrap();
+
+        let everything = transactions_page(&conn, &query(None, None, total)).await;
+        assert_eq!(everything.items.len() as u64, total);
+        assert_eq!(everything.next, None);
+        assert_eq!(everything.prev, None);
+
+        let first = transactions_page(&conn, &query(None, None, total - 1)).await;
+        let last = transactions_page(&conn, &query(first.next.as_deref(), None, total - 1)).await;
+        assert_eq!(last.items.len(), 1);
+        assert_eq!(last.next, None);
+        assert!(last.prev.is_some());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::AppState;
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use chrono::Utc;
+use serde::Serialize;
+use std::{collections::BTreeMap, time::Duration};
+use tokio::{net::TcpStream, time::timeout};
+
+const NODE_TIMEOUT: Duration = Duration::from_secs(2);
+const DEFAULT_MAX_STATE_AGE_SECS: i64 = 300;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Ok,
+    Fail,
+}
+
+#[derive(Debug, Serialize)]
+struct Check {
+    status: Status,
+    message: String,
+}
+
+impl Check {
+    fn ok(message: impl Into<String>) -> Self {
+        Check {
+            status: Status::Ok,
+            message: message.into(),
+        }
+    }
+
+    fn fail(message: impl Into<String>) -> Self {
+        Check {
+            status: Status::Fail,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    status: Status,
+    checks: BTreeMap<&'static str, Check>,
+}
+
+/// Liveness probe: answers as long as the process is serving requests.
+pub async fn healthz_handler() -> impl IntoResponse {
+    Json(Report {
+        status: Status::Ok,
+        checks: BTreeMap::new(),
+    })
+}
+
+/// Readiness probe: checks the database, the freshness of the node state and
+/// that the node API is reachable. Responds 503 when any check fails.
+pub async fn readyz_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let mut checks = BTreeMap::new();
+
+    checks.insert("database", check_database(&state).await);
+    checks.insert("global_state", check_global_state(&state).await);
+    checks.insert("node", check_node(&state).await);
+
+    let ready = checks
+        .values()
+        .all(|check| matches!(check.status, Status::Ok));
+
+    let report = Report {
+        status: if ready { Status::Ok } else { Status::Fail },
+        checks,
+    };
+
+    let code = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (code, Json(report))
+}
+
+async fn check_database(state: &AppState) -> Check {
+    match state.database.ping().await {
+        Ok(()) => Check::ok("connected"),
+        Err(err) => Check::fail(err.to_string()),
+    }
+}
+
+async fn check_global_state(state: &AppState) -> Check {
+    let max_age = std::env::var("READY_MAX_STATE_AGE")
+        .ok()
+        .and_then(|age| age.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_MAX_STATE_AGE_SECS);
+
+    // Only the cached copy, which the background refresher keeps fresh.
+    let global_state = state.global_state.read().await.clone();
+    let age = (Utc::now().naive_utc() - global_state.last_state_fetch).num_seconds();
+
+    if age <= max_age {
+        Check::ok(format!("last fetched {}s ago", age))
+    } else if global_state.current_layer == 0 {
+        Check::fail("never fetched")
+    } else {
+        Check::fail(format!("last fetched {}s ago, limit is {}s", age, max_age))
+    }
+}
+
+async fn check_node(state: &AppState) -> Check {
+    let node_host = match &state.network.node_host {
+        Some(node_host) => node_host,
+        None => return Check::fail("no node host configured"),
+    };
+
+    match timeout(NODE_TIMEOUT, TcpStream::connect(node_host)).await {
+        Ok(Ok(_)) => Check::ok(format!("{} reachable", node_host)),
+        Ok(Err(err)) => Check::fail(format!("{}: {}", node_host, err)),
+        Err(_) => Check::fail(format!("{}: timed out", node_host)),
+    }
+}
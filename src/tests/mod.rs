@@ -0,0 +1,802 @@
+mod routes;
+
+use crate::{
+    app,
+    db_entities::*,
+    metrics,
+    networks::{networks_router, Network},
+    types::Address,
+    vm::{Template, TxStatus},
+    AppState, GlobalState,
+};
+use axum::{
+    body::{to_bytes, Body},
+    http::{HeaderMap, Request, StatusCode},
+    Router,
+};
+use chrono::Utc;
+use metrics_exporter_prometheus::PrometheusHandle;
+use sea_orm::{
+    ActiveModelTrait, ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DbBackend,
+    EntityTrait, IntoActiveModel, Schema,
+};
+use std::sync::OnceLock;
+use tower::ServiceExt;
+
+/// Coinbase and principal of most fixture rows.
+pub const ACCOUNT: &str = "00000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+/// Account holding a balance but with no transactions or rewards.
+pub const OTHER_ACCOUNT: &str = "00000000bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+/// Multisig account requiring 2 of the keys in [`MULTISIG_SIGNERS`].
+pub const MULTISIG_ACCOUNT: &str = "00000000dddddddddddddddddddddddddddddddddddddddd";
+pub const MULTISIG_SIGNERS: [[u8; 32]; 3] = [[0x21; 32], [0x22; 32], [0x23; 32]];
+/// Vault owned by [`MULTISIG_ACCOUNT`] vesting 100 SMH, 10 of them up front,
+/// from layer 4 to 24.
+pub const VAULT_ACCOUNT: &str = "00000000eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee";
+/// Valid address that is not in the fixture.
+pub const MISSING_ACCOUNT: &str = "00000000cccccccccccccccccccccccccccccccccccccccc";
+
+pub const LAST_LAYER: i32 = 12;
+pub const EMPTY_LAYER: i32 = 10;
+pub const NO_BLOCK_LAYER: i32 = 11;
+pub const LAST_CERTIFIED_LAYER: i32 = 9;
+/// Transactions of `ACCOUNT` the node holds but has not put in a layer yet.
+pub const PENDING_TXS: u8 = 3;
+/// Epochs with a beacon row; the row of [`NULL_BEACON_EPOCH`] has no value
+/// and epoch 3 has no row at all.
+pub const BEACON_EPOCHS: [i32; 3] = [1, 2, 4];
+pub const NULL_BEACON_EPOCH: i32 = 2;
+/// ATX in the active set of epoch 3 that the node never stored.
+pub const UNKNOWN_ATX: [u8; 32] = [0xf1; 32];
+/// Smesher of the epoch 3 ATX; the earlier ones are published by `0x70`.
+pub const NEW_SMESHER: [u8; 32] = [0x71; 32];
+/// PoET proofs by ref, service and round; a `None` service and round are
+/// only in the proof and the last proof does not decode.
+pub const POET_ROUNDS: [(u8, Option<&str>, &str); 4] = [
+    (0xa1, Some("poet-1"), "9"),
+    (0xa2, Some("poet-1"), "10"),
+    (0xa3, None, "3"),
+    (0xa4, Some("poet-2"), "10"),
+];
+/// Proof referenced by the epoch 2 ATX that the node never stored.
+pub const MISSING_POET: [u8; 32] = [0xa9; 32];
+/// Smesher caught equivocating in the hare; [`NEW_SMESHER`] published
+/// multiple ATXs.
+pub const HARE_EQUIVOCATOR: [u8; 32] = [0x72; 32];
+/// Layer and index of the fixture transaction whose execution failed.
+pub const FAILED_TX: (i32, u8) = (3, 1);
+/// Layer and index of the fixture spend of [`SPENT`] smidge to
+/// [`OTHER_ACCOUNT`], which succeeded.
+pub const SPEND_TX: (i32, u8) = (5, 1);
+pub const SPENT: u64 = 2_500_000_000;
+/// Pending transaction `i` pays a gas price of `i + 1` for up to this much gas.
+pub const PENDING_MAX_GAS: u64 = 5_000;
+
+pub fn address(hex: &str) -> Vec<u8> {
+    hex::decode(hex).unwrap()
+}
+
+/// SCALE compact encoding, for building fixture state.
+pub fn compact(value: u64) -> Vec<u8> {
+    match value {
+        0..=0x3f => vec![(value as u8) << 2],
+        0x40..=0x3fff => ((value as u16) << 2 | 0b01).to_le_bytes().to_vec(),
+        0x4000..=0x3fff_ffff => ((value as u32) << 2 | 0b10).to_le_bytes().to_vec(),
+        _ => {
+            let bytes = value.to_le_bytes();
+            let len = 8 - bytes.iter().rev().take_while(|byte| **byte == 0).count();
+            [vec![((len - 4) as u8) << 2 | 0b11], bytes[..len].to_vec()].concat()
+        }
+    }
+}
+
+pub fn multisig_state(required: u8, keys: &[[u8; 32]]) -> Vec<u8> {
+    [
+        compact(required as u64),
+        compact(keys.len() as u64),
+        keys.concat(),
+    ]
+    .concat()
+}
+
+pub fn vault_state(owner: &[u8], total: u64, initial: u64, start: u32, end: u32) -> Vec<u8> {
+    [
+        owner.to_vec(),
+        compact(total),
+        compact(initial),
+        compact(start as u64),
+        compact(end as u64),
+    ]
+    .concat()
+}
+
+/// Raw transaction spawning `template` with `state` as its arguments.
+pub fn spawn_tx(principal: &[u8], template: Template, nonce: u64, state: &[u8]) -> Vec<u8> {
+    [
+        compact(0),
+        principal.to_vec(),
+        compact(0),
+        template.address().0,
+        compact(nonce),
+        compact(1),
+        state.to_vec(),
+        vec![0x5e; 64],
+    ]
+    .concat()
+}
+
+/// Raw transaction spending `amount` from `principal` to `destination`.
+pub fn spend_tx(principal: &[u8], nonce: u64, destination: &[u8], amount: u64) -> Vec<u8> {
+    [
+        compact(0),
+        principal.to_vec(),
+        compact(16),
+        compact(nonce),
+        compact(1),
+        destination.to_vec(),
+        compact(amount),
+        vec![0x5e; 64],
+    ]
+    .concat()
+}
+
+/// Header of a spend from `principal`, valid in any layer.
+pub fn tx_header(
+    principal: &[u8],
+    template: Template,
+    nonce: u64,
+    max_gas: u64,
+    gas_price: u64,
+) -> Vec<u8> {
+    [
+        principal.to_vec(),
+        template.address().0,
+        compact(16),
+        compact(nonce),
+        compact(0),
+        compact(0),
+        compact(max_gas),
+        compact(gas_price),
+        compact(1_000_000_000),
+    ]
+    .concat()
+}
+
+/// Result of executing a transaction for `fee` smidge in `block`.
+pub fn tx_result(
+    status: TxStatus,
+    message: &str,
+    fee: u64,
+    block: &[u8],
+    layer: u32,
+    addresses: &[&[u8]],
+) -> Vec<u8> {
+    [
+        compact(status as u64),
+        compact(message.len() as u64),
+        message.as_bytes().to_vec(),
+        compact(fee),
+        compact(fee),
+        block.to_vec(),
+        compact(layer as u64),
+        compact(addresses.len() as u64),
+        addresses.concat(),
+    ]
+    .concat()
+}
+
+/// PoET proof of `round` proving `proven` leaves out of `leaf_count`.
+pub fn poet_proof(service: &[u8], round: &str, proven: usize, leaf_count: u64) -> Vec<u8> {
+    let leaf = [compact(32), vec![0x1e; 32]].concat();
+    [
+        vec![0x0f; 32],
+        compact(proven as u64),
+        leaf.repeat(proven),
+        compact(1),
+        leaf,
+        compact(leaf_count),
+        compact(service.len() as u64),
+        service.to_vec(),
+        compact(round.len() as u64),
+        round.as_bytes().to_vec(),
+        vec![0; 64],
+    ]
+    .concat()
+}
+
+/// Version 1 ATX published in `epoch` whose NIPost references `poet_ref`.
+pub fn atx_v1(epoch: u32, coinbase: &[u8], poet_ref: &[u8]) -> Vec<u8> {
+    let post = [compact(7), compact(3), vec![1, 2, 3], compact(9)].concat();
+    [
+        compact(epoch as u64),
+        compact(epoch as u64),
+        vec![0xe0; 64],
+        vec![1],
+        vec![0xe0; 32],
+        vec![0],
+        coinbase.to_vec(),
+        compact(4),
+        vec![1],
+        compact(2),
+        vec![0x0c; 64],
+        compact(5),
+        vec![1],
+        post,
+        vec![1],
+        compact(poet_ref.len() as u64),
+        poet_ref.to_vec(),
+        compact(1024),
+        vec![0],
+        vec![0],
+        vec![0x70; 32],
+        vec![0; 64],
+    ]
+    .concat()
+}
+
+/// Malfeasance proof of `kind` with two conflicting messages of `smesher`
+/// carrying `metadata`; the kinds without messages only have a placeholder.
+pub fn malfeasance_proof(layer: u32, kind: u8, smesher: &[u8], metadata: &[u8]) -> Vec<u8> {
+    let data = match kind {
+        1..=3 => [metadata, &[0x0d; 32], smesher, &[0; 64]]
+            .concat()
+            .repeat(2),
+        _ => vec![0; 8],
+    };
+    [compact(layer as u64), vec![kind], data].concat()
+}
+
+/// Active set of `epoch` listing `atxs`.
+pub fn active_set(epoch: u32, atxs: &[&[u8]]) -> Vec<u8> {
+    [
+        compact(epoch as u64),
+        compact(atxs.len() as u64),
+        atxs.concat(),
+    ]
+    .concat()
+}
+
+/// Spawn arguments, and so state, of [`VAULT_ACCOUNT`].
+fn vault_account_state() -> Vec<u8> {
+    vault_state(
+        &address(MULTISIG_ACCOUNT),
+        100_000_000_000,
+        10_000_000_000,
+        4,
+        24,
+    )
+}
+
+fn filled<const N: usize>(byte: u8) -> Vec<u8> {
+    vec![byte; N]
+}
+
+async fn create_tables(conn: &DatabaseConnection) {
+    let schema = Schema::new(DbBackend::Sqlite);
+    let statements = [
+        schema.create_table_from_entity(accounts::Entity),
+        schema.create_table_from_entity(activesets::Entity),
+        schema.create_table_from_entity(atxs::Entity),
+        schema.create_table_from_entity(ballots::Entity),
+        schema.create_table_from_entity(beacons::Entity),
+        schema.create_table_from_entity(block_transactions::Entity),
+        schema.create_table_from_entity(blocks::Entity),
+        schema.create_table_from_entity(certificates::Entity),
+        schema.create_table_from_entity(identities::Entity),
+        schema.create_table_from_entity(layers::Entity),
+        schema.create_table_from_entity(poets::Entity),
+        schema.create_table_from_entity(proposal_transactions::Entity),
+        schema.create_table_from_entity(proposals::Entity),
+        schema.create_table_from_entity(recovery::Entity),
+        schema.create_table_from_entity(rewards::Entity),
+        schema.create_table_from_entity(transactions::Entity),
+        schema.create_table_from_entity(transactions_results_addresses::Entity),
+    ];
+
+    for statement in statements {
+        conn.execute(conn.get_database_backend().build(&statement))
+            .await
+            .unwrap();
+    }
+}
+
+async fn insert<A, M>(conn: &DatabaseConnection, models: Vec<M>)
+where
+    A: ActiveModelTrait,
+    M: IntoActiveModel<A>,
+{
+    A::Entity::insert_many(models.into_iter().map(IntoActiveModel::into_active_model))
+        .exec(conn)
+        .await
+        .unwrap();
+}
+
+/// Builds a small synthetic `state.sql` in memory.
+///
+/// Layers 1 to 12 exist, 10 has an empty applied block, 11 and 12 are not
+/// processed yet and only layers up to 9 are certified. Every layer holds one
+/// block, two transactions from [`ACCOUNT`] and one reward for it. Each
+/// transaction is proposed and included in its layer, and executed if the
+/// layer is processed. Pending transactions are only proposed.
+pub async fn fixture() -> DatabaseConnection {
+    let mut options = ConnectOptions::new("sqlite::memory:");
+    options.max_connections(1).min_connections(1);
+    let conn = Database::connect(options).await.unwrap();
+
+    create_tables(&conn).await;
+
+    let account = address(ACCOUNT);
+
+    insert::<layers::ActiveModel, _>(
+        &conn,
+        (1..=LAST_LAYER)
+            .map(|id| layers::Model {
+                id,
+                weak_coin: Some((id % 2) as i16),
+                processed: Some((id < NO_BLOCK_LAYER) as i16),
+                applied_block: match id {
+                    EMPTY_LAYER => Some(filled::<20>(0)),
+                    NO_BLOCK_LAYER.. => None,
+                    _ => Some(filled::<20>(id as u8)),
+                },
+                state_hash: match id {
+                    EMPTY_LAYER => Some(filled::<32>(0x50 + EMPTY_LAYER as u8 - 1)),
+                    NO_BLOCK_LAYER.. => None,
+                    _ => Some(filled::<32>(0x50 + id as u8)),
+                },
+                aggregated_hash: (id < NO_BLOCK_LAYER).then(|| filled::<32>(0xa0 + id as u8)),
+            })
+            .collect(),
+    )
+    .await;
+
+    insert::<blocks::ActiveModel, _>(
+        &conn,
+        (1..=LAST_LAYER)
+            .map(|layer| blocks::Model {
+                id: filled::<20>(layer as u8),
+                layer,
+                validity: Some(1),
+                block: Some(vec![0xb1, 0x0c, layer as u8]),
+            })
+            .collect(),
+    )
+    .await;
+
+    insert::<certificates::ActiveModel, _>(
+        &conn,
+        (1..=LAST_CERTIFIED_LAYER)
+            .map(|layer| certificates::Model {
+                layer,
+                block: filled::<20>(layer as u8),
+                cert: Some(vec![0xce]),
+                valid: 1,
+            })
+            .collect(),
+    )
+    .await;
+
+    insert::<transactions::ActiveModel, _>(
+        &conn,
+        (1..=LAST_LAYER)
+            .flat_map(|layer| {
+                let account = account.clone();
+                (0..2u8).map(move |index| {
+                    let nonce = (layer as u64) * 2 + index as u64;
+                    transactions::Model {
+                        id: filled::<32>((layer as u8) * 2 + index),
+                        tx: Some(match (layer, index) {
+                            (1, 0) => spawn_tx(&account, Template::Wallet, nonce, &[0x11; 32]),
+                            FAILED_TX => spend_tx(&account, nonce, &address(OTHER_ACCOUNT), 1),
+                            SPEND_TX => spend_tx(&account, nonce, &address(OTHER_ACCOUNT), SPENT),
+                            _ => vec![0x01, index],
+                        }),
+                        header: Some(vec![0x02, index]),
+                        result: (layer < NO_BLOCK_LAYER).then(|| {
+                            let (status, message) = match (layer, index) {
+                                FAILED_TX => (TxStatus::Failure, "insufficient funds"),
+                                _ => (TxStatus::Success, ""),
+                            };
+                            let block = filled::<20>(layer as u8);
+                            tx_result(status, message, 100, &block, layer as u32, &[&account])
+                        }),
+                        layer: Some(layer),
+                        block: Some(filled::<20>(layer as u8)),
+                        principal: Some(account.clone()),
+                        nonce: Some(nonce.to_be_bytes().to_vec()),
+                        timestamp: 1_700_000_000_000_000_000 + layer as i64 * 300_000_000_000,
+                    }
+                })
+            })
+            .collect(),
+    )
+    .await;
+
+    insert::<transactions::ActiveModel, _>(
+        &conn,
+        (0..PENDING_TXS)
+            .map(|index| transactions::Model {
+                id: filled::<32>(0xf0 + index),
+                tx: Some(vec![0x01, index]),
+                header: Some(tx_header(
+                    &account,
+                    Template::Wallet,
+                    LAST_LAYER as u64 * 2 + 2 + index as u64,
+                    PENDING_MAX_GAS,
+                    index as u64 + 1,
+                )),
+                result: None,
+                layer: None,
+                block: None,
+                principal: Some(account.clone()),
+                nonce: Some(
+                    (LAST_LAYER as u64 * 2 + 2 + index as u64)
+                        .to_be_bytes()
+                        .to_vec(),
+                ),
+                timestamp: 1_700_000_000_000_000_000 + (LAST_LAYER as i64 + 1) * 300_000_000_000,
+            })
+            .collect(),
+    )
+    .await;
+
+    let layer_txs = || (1..=LAST_LAYER).flat_map(|layer| (0..2u8).map(move |index| (layer, index)));
+    insert::<proposal_transactions::ActiveModel, _>(
+        &conn,
+        layer_txs()
+            .map(|(layer, index)| proposal_transactions::Model {
+                tid: filled::<32>((layer as u8) * 2 + index),
+                pid: filled::<20>(0xc0 + layer as u8),
+                layer,
+            })
+            .chain((0..PENDING_TXS).map(|index| proposal_transactions::Model {
+                tid: filled::<32>(0xf0 + index),
+                pid: filled::<20>(0xc0 + LAST_LAYER as u8),
+                layer: LAST_LAYER,
+            }))
+            .collect(),
+    )
+    .await;
+    insert::<block_transactions::ActiveModel, _>(
+        &conn,
+        layer_txs()
+            .map(|(layer, index)| block_transactions::Model {
+                tid: filled::<32>((layer as u8) * 2 + index),
+                bid: filled::<20>(layer as u8),
+                layer,
+            })
+            .collect(),
+    )
+    .await;
+
+    let vault_spawn = transactions::Model {
+        id: filled::<32>(0xd2),
+        tx: Some(spawn_tx(
+            &address(MULTISIG_ACCOUNT),
+            Template::Vault,
+            0,
+            &vault_account_state(),
+        )),
+        header: None,
+        result: None,
+        layer: Some(2),
+        block: Some(filled::<20>(2)),
+        principal: Some(address(MULTISIG_ACCOUNT)),
+        nonce: Some(0u64.to_be_bytes().to_vec()),
+        timestamp: 1_700_000_000_000_000_000 + 2 * 300_000_000_000,
+    };
+    insert::<transactions_results_addresses::ActiveModel, _>(
+        &conn,
+        [MULTISIG_ACCOUNT, VAULT_ACCOUNT]
+            .into_iter()
+            .map(|account| transactions_results_addresses::Model {
+                address: address(account),
+                tid: vault_spawn.id.clone(),
+            })
+            .collect(),
+    )
+    .await;
+    insert::<transactions::ActiveModel, _>(&conn, vec![vault_spawn]).await;
+
+    insert::<rewards::ActiveModel, _>(
+        &conn,
+        (1..=LAST_LAYER)
+            .map(|layer| rewards::Model {
+                coinbase: account.clone(),
+                layer,
+                total_reward: Some(layer as i64 * 1_000_000_000 + 500_000_000),
+                layer_reward: Some(1_000_000_000),
+            })
+            .collect(),
+    )
+    .await;
+
+    insert::<accounts::ActiveModel, _>(
+        &conn,
+        vec![
+            accounts::Model {
+                address: account.clone(),
+                balance: 12_500_000_000,
+                next_nonce: 26,
+                layer_updated: 12,
+                template: Some([vec![0; 23], vec![1]].concat()),
+                state: Some(filled::<32>(0x11)),
+            },
+            accounts::Model {
+                address: address(OTHER_ACCOUNT),
+                balance: 1_000_000_000,
+                next_nonce: 0,
+                layer_updated: 3,
+                template: None,
+                state: None,
+            },
+            accounts::Model {
+                address: address(MULTISIG_ACCOUNT),
+                balance: 0,
+                next_nonce: 1,
+                layer_updated: 2,
+                template: Some([vec![0; 23], vec![2]].concat()),
+                state: Some(multisig_state(2, &MULTISIG_SIGNERS)),
+            },
+            accounts::Model {
+                address: address(VAULT_ACCOUNT),
+                balance: 100_000_000_000,
+                next_nonce: 0,
+                layer_updated: 2,
+                template: Some([vec![0; 23], vec![4]].concat()),
+                state: Some(vault_account_state()),
+            },
+        ],
+    )
+    .await;
+
+    insert::<beacons::ActiveModel, _>(
+        &conn,
+        BEACON_EPOCHS
+            .into_iter()
+            .map(|epoch| beacons::Model {
+                epoch,
+                beacon: (epoch != NULL_BEACON_EPOCH).then(|| vec![0xbe, 0, 0, epoch as u8]),
+            })
+            .collect(),
+    )
+    .await;
+
+    insert::<poets::ActiveModel, _>(
+        &conn,
+        POET_ROUNDS
+            .into_iter()
+            .map(|(proof, service, round)| poets::Model {
+                r#ref: hex::encode([proof; 32]),
+                poet: Some(if proof == 0xa4 {
+                    vec![1, 2, 3]
+                } else {
+                    poet_proof(&[0x5e; 4], round, 2, 4096)
+                }),
+                service_id: service.map(String::from),
+                round_id: (proof != 0xa3).then(|| round.to_string()),
+            })
+            .collect(),
+    )
+    .await;
+
+    let identities = [
+        (
+            NEW_SMESHER,
+            Some(malfeasance_proof(13, 1, &NEW_SMESHER, &compact(3))),
+            1_700_000_100,
+        ),
+        (
+            HARE_EQUIVOCATOR,
+            Some(malfeasance_proof(
+                6,
+                3,
+                &HARE_EQUIVOCATOR,
+                &[compact(5), compact(2)].concat(),
+            )),
+            1_700_000_200,
+        ),
+        (
+            [0x73; 32],
+            Some(malfeasance_proof(8, 4, &[], &[])),
+            1_700_000_000,
+        ),
+        ([0x74; 32], None, 1_700_000_300),
+    ];
+    insert::<identities::ActiveModel, _>(
+        &conn,
+        identities
+            .into_iter()
+            .map(|(pubkey, proof, received)| identities::Model {
+                pubkey: hex::encode(pubkey),
+                proof,
+                received,
+            })
+            .collect(),
+    )
+    .await;
+
+    // Epoch 3 lists the same ATX twice, in two different stored sets.
+    let active_sets: [(i32, Vec<&[u8]>); 4] = [
+        (2, vec![&[0xe1; 32]]),
+        (3, vec![&[0xe2; 32], &UNKNOWN_ATX]),
+        (3, vec![&[0xe2; 32]]),
+        (4, vec![&[0xe3; 32]]),
+    ];
+    insert::<activesets::ActiveModel, _>(
+        &conn,
+        active_sets
+            .into_iter()
+            .enumerate()
+            .map(|(i, (epoch, atxs))| activesets::Model {
+                id: filled::<32>(0xa0 + i as u8),
+                active_set: Some(active_set(epoch as u32, &atxs)),
+                epoch,
+            })
+            .collect(),
+    )
+    .await;
+
+    insert::<atxs::ActiveModel, _>(
+        &conn,
+        (1..=3)
+            .map(|epoch| atxs::Model {
+                id: filled::<32>(0xe0 + epoch as u8),
+                epoch,
+                effective_num_units: 4 * epoch,
+                commitment_atx: None,
+                nonce: Some(7),
+                base_tick_height: Some(100),
+                tick_count: Some(10),
+                sequence: Some(epoch as i64),
+                pubkey: Some(if epoch == 3 {
+                    NEW_SMESHER.to_vec()
+                } else {
+                    filled::<32>(0x70)
+                }),
+                coinbase: Some(account.clone()),
+                atx: match epoch {
+                    2 => Some(atx_v1(2, &account, &MISSING_POET)),
+                    3 => Some(atx_v1(3, &account, &[POET_ROUNDS[1].0; 32])),
+                    _ => None,
+                },
+                received: 0,
+            })
+            .collect(),
+    )
+    .await;
+
+    conn
+}
+
+pub async fn test_app() -> Router {
+    test_app_with(fixture().await).await
+}
+
+/// The process-wide recorder, installed once so every test app records into
+/// the same registry as in production.
+fn recorder() -> PrometheusHandle {
+    static RECORDER: OnceLock<PrometheusHandle> = OnceLock::new();
+    RECORDER
+        .get_or_init(|| metrics::install_recorder().unwrap())
+        .clone()
+}
+
+/// Network of the fixture apps.
+pub const TEST_NETWORK: &str = "mainnet";
+
+pub fn test_network(name: &str) -> Network {
+    Network {
+        name: name.to_string(),
+        database_url: "sqlite::memory:".to_string(),
+        node_host: None,
+        grpcurl: "grpcurl".to_string(),
+        hrp: "sm".to_string(),
+        pool: Default::default(),
+    }
+}
+
+pub async fn test_state(mut conn: DatabaseConnection) -> AppState {
+    metrics::track_database(&mut conn);
+    let state = AppState::new(
+        conn,
+        recorder(),
+        test_network(TEST_NETWORK),
+        vec![TEST_NETWORK.to_string()],
+    );
+
+    *state.global_state.write().await = GlobalState {
+        highest_atx: "e3".repeat(32),
+        previous_atx: "e2".repeat(32),
+        genesis_timestamp: "1689310800".into(),
+        genesis_time: "2023-07-14 05:00:00".into(),
+        current_layer: 14,
+        current_epoch: 3,
+        epoch_num_layers: 4,
+        layer_duration: "300".into(),
+        last_state_fetch: Utc::now().naive_utc(),
+    };
+
+    state
+}
+
+pub async fn test_app_with(conn: DatabaseConnection) -> Router {
+    app(test_state(conn).await)
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+pub async fn get(app: &Router, uri: &str) -> TestResponse {
+    send(app, Request::get(uri).body(Body::empty()).unwrap()).await
+}
+
+pub async fn send(app: &Router, request: Request<Body>) -> TestResponse {
+    let response = app.clone().oneshot(request).await.unwrap();
+
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+    TestResponse {
+        status,
+        headers,
+        // Lossy so compressed bodies can be checked by their headers.
+        body: String::from_utf8_lossy(&body).into_owned(),
+    }
+}
+
+/// Compares `body` with `tests/golden/<name>`. Run the tests with
+/// `UPDATE_GOLDEN=1` to rewrite the files after an intended change.
+/// Marker present once in every rendered transaction row.
+pub const TX_ROW: &str = "<h4>Nonce:</h4>";
+
+/// Cursor of the pagination link using `direction` (`after=` or `before=`).
+pub fn link_cursor(body: &str, direction: &str) -> Option<String> {
+    let start = body.find(direction)? + direction.len();
+    Some(body[start..].split('&').next()?.to_string())
+}
+
+/// Follows pagination links in `direction` starting from the first page of
+/// `url`, returning every page body.
+pub async fn walk(app: &Router, url: &str, direction: &str, limit: u64) -> Vec<String> {
+    let mut pages = vec![];
+    let mut uri = format!("{}?limit={}", url, limit);
+
+    loop {
+        let page = get(app, &uri).await;
+        assert_eq!(page.status, StatusCode::OK);
+        let cursor = link_cursor(&page.body, direction);
+        pages.push(page.body);
+
+        match cursor {
+            Some(cursor) => uri = format!("{}?{}{}&limit={}", url, direction, cursor, limit),
+            None => break,
+        }
+        assert!(pages.len() < 100, "pagination does not terminate");
+    }
+
+    pages
+}
+
+pub fn assert_golden(name: &str, body: &str) {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name);
+
+    if std::env::var("UPDATE_GOLDEN").is_ok() {
+        std::fs::write(&path, body).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing golden file {}, run with UPDATE_GOLDEN=1",
+            path.display()
+        )
+    });
+    assert_eq!(body, expected, "response differs from {}", path.display());
+}
@@ -0,0 +1,1181 @@
+use super::*;
+use sea_orm::{ColumnTrait, PaginatorTrait, QueryFilter, Set};
+
+#[tokio::test]
+async fn home_page_and_fragments() {
+    let app = test_app().await;
+
+    let page = get(&app, "/").await;
+    assert_eq!(page.status, StatusCode::OK);
+    assert!(page.body.contains("hx-get=\"/home/layers\""));
+
+    let layers = get(&app, "/home/layers").await;
+    assert_eq!(layers.status, StatusCode::OK);
+    assert_golden("home_layers.html", &layers.body);
+
+    let transactions = get(&app, "/home/transactions").await;
+    assert_eq!(transactions.status, StatusCode::OK);
+    assert_golden("home_transactions.html", &transactions.body);
+
+    let rewards = get(&app, "/home/rewards").await;
+    assert_eq!(rewards.status, StatusCode::OK);
+    assert_golden("home_rewards.html", &rewards.body);
+
+    let global_state = get(&app, "/home/global_state").await;
+    assert_eq!(global_state.status, StatusCode::OK);
+    assert!(global_state.body.contains("Layer: 14"));
+    assert!(global_state.body.contains("2023-07-14 05:00:00"));
+}
+
+#[tokio::test]
+async fn layer_page_and_tabs() {
+    let app = test_app().await;
+
+    let page = get(&app, "/layer/5").await;
+    assert_eq!(page.status, StatusCode::OK);
+    assert!(page.body.contains("href=\"/layer/4\""));
+    assert!(page.body.contains("href=\"/layer/6\""));
+
+    let layer = get(&app, "/layer/5/layer").await;
+    assert_eq!(layer.status, StatusCode::OK);
+    assert_golden("layer_tab.html", &layer.body);
+
+    let blocks = get(&app, "/layer/5/blocks").await;
+    assert_eq!(blocks.status, StatusCode::OK);
+    assert!(blocks.body.contains(&"05".repeat(20)));
+    assert!(blocks.body.contains("1 total"));
+
+    let transactions = get(&app, "/layer/5/transactions").await;
+    assert_eq!(transactions.status, StatusCode::OK);
+    assert!(transactions.body.contains("2 total"));
+    assert!(transactions.body.contains(ACCOUNT));
+
+    let rewards = get(&app, "/layer/5/rewards").await;
+    assert_eq!(rewards.status, StatusCode::OK);
+    assert_golden("layer_rewards.html", &rewards.body);
+}
+
+#[tokio::test]
+async fn layer_edge_cases() {
+    let app = test_app().await;
+
+    let missing = get(&app, "/layer/999/layer").await;
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+    assert!(missing.body.contains("Layer 999 not found"));
+
+    let empty = get(&app, "/layer/999/transactions").await;
+    assert_eq!(empty.status, StatusCode::OK);
+    assert!(empty.body.contains("0 total"));
+
+    let malformed = get(&app, "/layer/abc/layer").await;
+    assert_eq!(malformed.status, StatusCode::BAD_REQUEST);
+
+    let first = get(&app, "/layer/1").await;
+    assert_eq!(first.status, StatusCode::OK);
+    assert!(!first.body.contains("href=\"/layer/0\""));
+}
+
+#[tokio::test]
+async fn layers_browser() {
+    let app = test_app().await;
+
+    let page = get(&app, "/layers").await;
+    assert_eq!(page.status, StatusCode::OK);
+
+    let list = get(&app, "/layers/list?limit=5").await;
+    assert_eq!(list.status, StatusCode::OK);
+    assert!(list.body.contains("#12"));
+    assert!(list.body.contains("#8"));
+    assert!(!list.body.contains("#7<"));
+    assert!(list.body.contains("after=8&limit=5"));
+
+    let next = get(&app, "/layers/list?after=8&limit=5").await;
+    assert!(next.body.contains("#7"));
+    assert!(next.body.contains("before=7&limit=5"));
+
+    let jump = get(&app, "/layers/list?from=3").await;
+    assert!(jump.body.contains("#3"));
+    assert!(!jump.body.contains("#4<"));
+
+    let empty = get(&app, "/layers/list?filter=empty").await;
+    assert!(empty.body.contains(&format!("#{}", EMPTY_LAYER)));
+    assert!(empty.body.contains("1 total"));
+
+    let unprocessed = get(&app, "/layers/list?filter=unprocessed").await;
+    assert!(unprocessed.body.contains("2 total"));
+
+    let invalid = get(&app, "/layers/list?filter=bogus").await;
+    assert_eq!(invalid.status, StatusCode::BAD_REQUEST);
+
+    let blank_jump = get(&app, "/layers/list?filter=processed&from=").await;
+    assert_eq!(blank_jump.status, StatusCode::OK);
+    assert!(blank_jump
+        .body
+        .contains(&format!("#{}", NO_BLOCK_LAYER - 1)));
+
+    let bad_jump = get(&app, "/layers/list?from=abc").await;
+    assert_eq!(bad_jump.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn account_page_and_tabs() {
+    let app = test_app().await;
+
+    let page = get(&app, &format!("/account/{}", ACCOUNT)).await;
+    assert_eq!(page.status, StatusCode::OK);
+
+    let account = get(&app, &format!("/account/{}/account", ACCOUNT)).await;
+    assert_eq!(account.status, StatusCode::OK);
+    assert_golden("account_tab.html", &account.body);
+
+    let transactions = get(&app, &format!("/account/{}/transactions?limit=3", ACCOUNT)).await;
+    assert_eq!(transactions.status, StatusCode::OK);
+    assert_golden("account_transactions.html", &transactions.body);
+
+    let rewards = get(&app, &format!("/account/{}/rewards?limit=2", ACCOUNT)).await;
+    assert_eq!(rewards.status, StatusCode::OK);
+    assert!(rewards.body.contains("12 total"));
+    assert!(rewards.body.contains(&format!("after=11-{}", ACCOUNT)));
+
+    let atxs = get(&app, &format!("/account/{}/atxs", ACCOUNT)).await;
+    assert_eq!(atxs.status, StatusCode::OK);
+    assert!(atxs.body.contains("3 total"));
+    assert!(atxs.body.contains(&"e3".repeat(32)));
+}
+
+#[tokio::test]
+async fn account_template_state() {
+    let app = test_app().await;
+
+    let wallet = get(&app, &format!("/account/{}/account", ACCOUNT)).await;
+    assert!(wallet.body.contains("<p>Wallet</p>"));
+    assert!(wallet.body.contains(&"11".repeat(32)));
+
+    let multisig = get(&app, &format!("/account/{}/account", MULTISIG_ACCOUNT)).await;
+    assert_eq!(multisig.status, StatusCode::OK);
+    assert_golden("account_multisig.html", &multisig.body);
+    assert!(multisig.body.contains("2 of 3"));
+    for key in MULTISIG_SIGNERS {
+        let wallet = crate::vm::principal(crate::vm::Template::Wallet, &key);
+        assert!(multisig
+            .body
+            .contains(&format!("href=\"/account/{}\"", wallet)));
+    }
+
+    let vault = get(&app, &format!("/account/{}/account", VAULT_ACCOUNT)).await;
+    assert_eq!(vault.status, StatusCode::OK);
+    assert_golden("account_vault.html", &vault.body);
+    assert!(vault
+        .body
+        .contains(&format!("href=\"/account/{}\"", MULTISIG_ACCOUNT)));
+    assert!(vault.body.contains("<p>55 SMH</p>"));
+    assert!(vault.body.contains("<p>45 SMH</p>"));
+}
+
+#[tokio::test]
+async fn account_creation() {
+    let app = test_app().await;
+
+    let wallet = get(&app, &format!("/account/{}/account", ACCOUNT)).await;
+    assert!(wallet.body.contains(&"02".repeat(32)));
+    assert!(wallet.body.contains("Self-spawn"));
+    assert_eq!(wallet.body.matches("<p>Layer 1</p>").count(), 2);
+
+    let vault = get(&app, &format!("/account/{}/account", VAULT_ACCOUNT)).await;
+    assert!(vault.body.contains(&"d2".repeat(32)));
+    assert_eq!(vault.body.matches("<p>Layer 2</p>").count(), 2);
+    assert!(!vault.body.contains("Self-spawn"));
+    assert!(vault
+        .body
+        .contains(&format!("<p>{}</p>\n        </a>", MULTISIG_ACCOUNT)));
+
+    let multisig = get(&app, &format!("/account/{}/account", MULTISIG_ACCOUNT)).await;
+    assert!(multisig.body.contains("<p>Unknown</p>"));
+
+    let stub = get(&app, &format!("/account/{}/account", OTHER_ACCOUNT)).await;
+    assert!(stub.body.contains("Not spawned yet"));
+    assert!(stub.body.contains("href=\"/layer/3\""));
+}
+
+#[tokio::test]
+async fn account_edge_cases() {
+    let app = test_app().await;
+
+    let malformed = get(&app, "/account/not-hex/account").await;
+    assert_eq!(malformed.status, StatusCode::BAD_REQUEST);
+    assert!(malformed.body.contains("Invalid id"));
+
+    let malformed = get(&app, "/account/zz/transactions").await;
+    assert_eq!(malformed.status, StatusCode::BAD_REQUEST);
+
+    let truncated = get(&app, &format!("/account/{}/rewards", &ACCOUNT[..40])).await;
+    assert_eq!(truncated.status, StatusCode::BAD_REQUEST);
+
+    let missing = get(&app, &format!("/account/{}/account", MISSING_ACCOUNT)).await;
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+
+    let no_rewards = get(&app, &format!("/account/{}/rewards", OTHER_ACCOUNT)).await;
+    assert_eq!(no_rewards.status, StatusCode::OK);
+    assert!(no_rewards.body.contains("0 total"));
+
+    let bad_cursor = get(
+        &app,
+        &format!("/account/{}/rewards?after=nonsense", ACCOUNT),
+    )
+    .await;
+    assert_eq!(bad_cursor.status, StatusCode::OK);
+    assert!(bad_cursor.body.contains(">12<"));
+}
+
+#[tokio::test]
+async fn account_transactions_reach_pending() {
+    let app = test_app().await;
+    let url = format!("/account/{}/transactions", ACCOUNT);
+    let expected = LAST_LAYER as usize * 2 + PENDING_TXS as usize;
+
+    let first = get(&app, &format!("{}?limit=5", url)).await;
+    assert_eq!(
+        first.body.matches("<p>Pending</p>").count(),
+        PENDING_TXS as usize
+    );
+
+    let pages = walk(&app, &url, "after=", 5).await;
+    let rows: usize = pages.iter().map(|body| body.matches(TX_ROW).count()).sum();
+    assert!(pages[0].contains(&format!("{} total", expected)));
+    assert_eq!(rows, expected);
+}
+
+#[tokio::test]
+async fn mempool_page_and_api() {
+    let app = test_app().await;
+
+    let page = get(&app, &format!("/mempool?principal={}", ACCOUNT)).await;
+    assert_eq!(page.status, StatusCode::OK);
+    assert!(page
+        .body
+        .contains(&format!("hx-get=\"/mempool/list?principal={}\"", ACCOUNT)));
+
+    let list = get(&app, "/mempool/list?limit=2").await;
+    assert_eq!(list.status, StatusCode::OK);
+    assert!(list.body.contains(&format!("{} total", PENDING_TXS)));
+    assert_eq!(list.body.matches("Max fee:").count(), 2);
+    assert!(list.body.contains("0.000015 SMH"));
+    assert!(list.body.contains("(3/gas)"));
+    assert!(link_cursor(&list.body, "after=").is_some());
+
+    let other = get(&app, &format!("/mempool/list?principal={}", OTHER_ACCOUNT)).await;
+    assert!(other.body.contains("0 total"));
+    assert!(other.body.contains("No pending transactions."));
+
+    let malformed = get(&app, "/mempool/list?principal=zz").await;
+    assert_eq!(malformed.status, StatusCode::BAD_REQUEST);
+
+    let api = get(&app, &format!("/api/mempool?limit=2&principal={}", ACCOUNT)).await;
+    assert_eq!(api.status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&api.body).unwrap();
+    assert_eq!(json["total"], PENDING_TXS as u64);
+    assert_eq!(json["items"].as_array().unwrap().len(), 2);
+    assert_eq!(json["items"][0]["principal"], ACCOUNT);
+    assert_eq!(json["items"][0]["max_fee"], "15000");
+    assert_eq!(json["items"][0]["gas_price"], 3);
+
+    let next = json["next"].as_str().unwrap();
+    let rest = get(&app, &format!("/api/mempool?limit=2&after={}", next)).await;
+    let rest: serde_json::Value = serde_json::from_str(&rest.body).unwrap();
+    assert_eq!(rest["items"].as_array().unwrap().len(), 1);
+    assert_eq!(rest["next"], serde_json::Value::Null);
+
+    let malformed = get(&app, "/api/mempool?principal=zz").await;
+    assert_eq!(malformed.status, StatusCode::BAD_REQUEST);
+    let error: serde_json::Value = serde_json::from_str(&malformed.body).unwrap();
+    assert!(error["error"].as_str().unwrap().contains("Invalid id"));
+
+    let account = get(&app, &format!("/account/{}/account", ACCOUNT)).await;
+    assert!(account
+        .body
+        .contains(&format!("{} transactions in the mempool", PENDING_TXS)));
+}
+
+#[tokio::test]
+async fn transaction_page_and_timeline() {
+    let app = test_app().await;
+    let applied = "0a".repeat(32);
+
+    let page = get(&app, &format!("/tx/{}", applied)).await;
+    assert_eq!(page.status, StatusCode::OK);
+    assert!(page
+        .body
+        .contains(&format!("hx-get=\"/tx/{}/timeline\"", applied)));
+
+    let details = get(&app, &format!("/tx/{}/transaction", applied)).await;
+    assert_eq!(details.status, StatusCode::OK);
+    assert!(details.body.contains("<p>Success</p>"));
+
+    let timeline = get(&app, &format!("/tx/{}/timeline", applied)).await;
+    assert_eq!(timeline.status, StatusCode::OK);
+    assert_golden("tx_timeline.html", &timeline.body);
+
+    let failed = "07".repeat(32);
+    let details = get(&app, &format!("/tx/{}/transaction", failed)).await;
+    assert_golden("tx_failed.html", &details.body);
+    assert!(details.body.contains("Failure: insufficient funds"));
+    assert!(details
+        .body
+        .contains(&format!("href=\"/account/{}\"", OTHER_ACCOUNT)));
+
+    let unprocessed = get(&app, &format!("/tx/{}/timeline", "18".repeat(32))).await;
+    assert!(unprocessed.body.contains("(not applied)"));
+    assert!(unprocessed.body.contains("Not executed yet"));
+
+    let pending = get(&app, &format!("/tx/{}/timeline", "f0".repeat(32))).await;
+    assert!(pending.body.contains(&format!("layer {}", LAST_LAYER)));
+    assert!(pending.body.contains("Not in a block yet"));
+    assert!(pending.body.contains("Not executed yet"));
+
+    let spawn = get(&app, &format!("/tx/{}/transaction", "02".repeat(32))).await;
+    assert!(spawn.body.contains("Spawn Wallet"));
+
+    let missing = get(&app, &format!("/tx/{}/timeline", "99".repeat(32))).await;
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+
+    let malformed = get(&app, "/tx/abc/transaction").await;
+    assert_eq!(malformed.status, StatusCode::BAD_REQUEST);
+
+    let search = get(&app, &format!("/search?input={}", applied.to_uppercase())).await;
+    assert_eq!(
+        search.headers["HX-Redirect"],
+        format!("/tx/{}", applied).as_str()
+    );
+}
+
+#[tokio::test]
+async fn beacons_epochs_and_atxs() {
+    let app = test_app().await;
+
+    let page = get(&app, "/beacons").await;
+    assert_eq!(page.status, StatusCode::OK);
+
+    let list = get(&app, "/beacons/list").await;
+    assert_eq!(list.status, StatusCode::OK);
+    assert_golden("beacons_list.html", &list.body);
+    assert!(list.body.contains("4 epochs, 2 without a beacon"));
+
+    let api = get(&app, "/api/beacons").await;
+    let json: serde_json::Value = serde_json::from_str(&api.body).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 4);
+    assert_eq!(json[0]["epoch"], 4);
+    assert_eq!(json[0]["beacon"], "be000004");
+    assert_eq!(json[1]["beacon"], serde_json::Value::Null);
+
+    let epoch = get(&app, &format!("/api/beacons/{}", NULL_BEACON_EPOCH)).await;
+    assert_eq!(epoch.status, StatusCode::OK);
+    assert!(epoch.body.contains(r#""beacon":null"#));
+    let unknown = get(&app, "/api/beacons/9").await;
+    assert_eq!(unknown.status, StatusCode::NOT_FOUND);
+    assert!(unknown.body.contains(r#""error""#));
+
+    let page = get(&app, "/epoch/3").await;
+    assert!(page.body.contains("href=\"/epoch/2\""));
+    assert!(page.body.contains("href=\"/epoch/4\""));
+
+    let epoch = get(&app, "/epoch/4/epoch").await;
+    assert_golden("epoch_tab.html", &epoch.body);
+    assert!(epoch.body.contains("be000004"));
+    assert!(epoch.body.contains("1 published in epoch 3"));
+    assert!(epoch.body.contains("href=\"/layer/19\""));
+    assert!(get(&app, "/epoch/3/epoch").await.body.contains("Missing"));
+
+    let first = get(&app, "/epoch/0/epoch").await;
+    assert_eq!(first.status, StatusCode::OK);
+    assert!(first.body.contains("href=\"/layer/0\""));
+    let last = get(&app, &format!("/epoch/{}/epoch", i32::MAX)).await;
+    assert_eq!(last.status, StatusCode::OK);
+    for uri in ["/epoch/-1/epoch", "/epoch/-2147483648/activeset"] {
+        assert_eq!(get(&app, uri).await.status, StatusCode::NOT_FOUND);
+    }
+
+    let atx = get(&app, &format!("/atx/{}/atx", "e3".repeat(32))).await;
+    assert_eq!(atx.status, StatusCode::OK);
+    assert!(atx.body.contains("be000004 (epoch 4)"));
+    let atx = get(&app, &format!("/atx/{}/atx", "e1".repeat(32))).await;
+    assert!(atx.body.contains("Missing for epoch 2"));
+
+    let missing = get(&app, &format!("/atx/{}/atx", "99".repeat(32))).await;
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+    let malformed = get(&app, "/atx/xyz/atx").await;
+    assert_eq!(malformed.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn epoch_active_sets() {
+    let app = test_app().await;
+
+    let page = get(&app, "/epoch/4").await;
+    assert!(page.body.contains("hx-get=\"/epoch/4/activeset\""));
+
+    // Both stored sets of epoch 3 are merged; the unknown ATX has no weight.
+    let merged = get(&app, "/epoch/3/activeset").await;
+    assert_eq!(merged.status, StatusCode::OK);
+    assert!(merged.body.contains("2 ATXs, 1 stored by this node"));
+    assert!(merged.body.contains("<p>80</p>"));
+    assert!(merged.body.contains("0 smeshers since epoch 2"));
+
+    let changed = get(&app, "/epoch/4/activeset").await;
+    assert_golden("active_set_tab.html", &changed.body);
+    assert!(changed.body.contains(&hex::encode(NEW_SMESHER)));
+    assert!(changed.body.contains(&"70".repeat(32)));
+
+    let first = get(&app, "/epoch/2/activeset").await;
+    assert!(!first.body.contains("since epoch"));
+    let none = get(&app, "/epoch/9/activeset").await;
+    assert!(none.body.contains("No active set stored for epoch 9"));
+
+    let check = |id: String| {
+        let app = app.clone();
+        async move { get(&app, &format!("/epoch/4/activeset/check?id={}", id)).await }
+    };
+    let atx = check("e3".repeat(32)).await;
+    assert!(atx.body.contains("is in the active set of epoch 4"));
+    let smesher = check(hex::encode(NEW_SMESHER)).await;
+    assert!(smesher
+        .body
+        .contains(&format!("href=\"/atx/{}\"", "e3".repeat(32))));
+    let absent = check("70".repeat(32)).await;
+    assert!(absent.body.contains("is not in the active set of epoch 4"));
+    let unstored = get(
+        &app,
+        &format!("/epoch/3/activeset/check?id={}", hex::encode(UNKNOWN_ATX)),
+    )
+    .await;
+    assert!(unstored.body.contains("is in the active set of epoch 3"));
+    assert_eq!(check("xyz".into()).await.status, StatusCode::BAD_REQUEST);
+
+    let unknown_epoch = get(
+        &app,
+        &format!("/epoch/9/activeset/check?id={}", "e3".repeat(32)),
+    )
+    .await;
+    assert!(unknown_epoch.body.contains("No active set stored"));
+}
+
+#[tokio::test]
+async fn poet_rounds() {
+    let app = test_app().await;
+
+    let page = get(&app, "/poets").await;
+    assert_eq!(page.status, StatusCode::OK);
+
+    let list = get(&app, "/poets/list").await;
+    assert_golden("poets_list.html", &list.body);
+    assert!(list.body.contains("2 rounds, latest 10"));
+    // Rounds sort numerically, and a missing service falls back to the proof.
+    let ten = list.body.find("10 of poet-1").unwrap();
+    assert!(ten < list.body.find("9 of poet-1").unwrap());
+    assert!(list.body.contains("3 of 5e5e5e5e"));
+    assert!(list.body.contains("Not decoded, 3 bytes"));
+
+    let round = get(&app, &format!("/poets/{}/round", "a2".repeat(32))).await;
+    assert_eq!(round.status, StatusCode::OK);
+    assert!(round.body.contains("<p>4096</p>"));
+    assert!(round.body.contains("2, with 1 proof nodes"));
+    let missing = get(&app, &format!("/poets/{}/round", "99".repeat(32))).await;
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+    assert_eq!(
+        get(&app, "/poets/xyz/round").await.status,
+        StatusCode::BAD_REQUEST
+    );
+
+    let served = get(&app, &format!("/atx/{}/atx", "e3".repeat(32))).await;
+    assert!(served
+        .body
+        .contains(&format!("href=\"/poets/{}\"", "a2".repeat(32))));
+    assert!(served.body.contains("Round 10 of poet-1"));
+    let unstored = get(&app, &format!("/atx/{}/atx", "e2".repeat(32))).await;
+    assert!(unstored
+        .body
+        .contains(&format!("{} (not stored)", hex::encode(MISSING_POET))));
+    let golden = get(&app, &format!("/atx/{}/atx", "e1".repeat(32))).await;
+    assert!(!golden.body.contains("PoET Proof"));
+}
+
+#[tokio::test]
+async fn malfeasance_and_smeshers() {
+    let app = test_app().await;
+
+    let page = get(&app, "/malfeasance").await;
+    assert_eq!(page.status, StatusCode::OK);
+
+    let list = get(&app, "/malfeasance/list").await;
+    assert_golden("malfeasance_list.html", &list.body);
+    assert!(list.body.contains(
+        "3 malicious identities, 1 Hare equivocation, 1 Invalid PoST index, 1 Multiple ATXs"
+    ));
+    assert!(!list.body.contains(&"74".repeat(32)));
+    // Newest proofs first.
+    let hare = list.body.find(&hex::encode(HARE_EQUIVOCATOR)).unwrap();
+    assert!(hare < list.body.find(&hex::encode(NEW_SMESHER)).unwrap());
+    assert!(list.body.contains("href=\"/layer/5\""));
+
+    let smesher = get(&app, &format!("/smesher/{}", hex::encode(NEW_SMESHER))).await;
+    assert_eq!(smesher.status, StatusCode::OK);
+    let flagged = get(
+        &app,
+        &format!("/smesher/{}/smesher", hex::encode(NEW_SMESHER)),
+    )
+    .await;
+    assert!(flagged.body.contains("Multiple ATXs in"));
+    assert!(flagged.body.contains("href=\"/epoch/3\""));
+    let honest = get(&app, &format!("/smesher/{}/smesher", "70".repeat(32))).await;
+    assert!(honest.body.contains("<p>2</p>"));
+    assert!(!honest.body.contains("Malicious"));
+    let atxs = get(&app, &format!("/smesher/{}/atxs", "70".repeat(32))).await;
+    assert!(atxs
+        .body
+        .contains(&format!("href=\"/atx/{}\"", "e1".repeat(32))));
+    assert!(!atxs.body.contains(&"e3".repeat(32)));
+
+    let unknown = get(&app, &format!("/smesher/{}/smesher", "99".repeat(32))).await;
+    assert_eq!(unknown.status, StatusCode::NOT_FOUND);
+    assert_eq!(
+        get(&app, "/smesher/xyz/smesher").await.status,
+        StatusCode::BAD_REQUEST
+    );
+
+    let atx = get(&app, &format!("/atx/{}/atx", "e3".repeat(32))).await;
+    assert!(atx.body.contains("Multiple ATXs in"));
+    let atx = get(&app, &format!("/atx/{}/atx", "e2".repeat(32))).await;
+    assert!(!atx.body.contains("Malicious"));
+}
+
+#[tokio::test]
+async fn network_analytics() {
+    let app = test_app().await;
+
+    let page = get(&app, "/analytics").await;
+    assert_eq!(page.status, StatusCode::OK);
+
+    let network = get(&app, "/analytics/network").await;
+    assert_golden("analytics_network.html", &network.body);
+    assert!(network.body.contains("0.75 TiB"));
+    assert!(network.body.contains("width: 100%"));
+
+    let api = get(&app, "/api/analytics/network").await;
+    let json: serde_json::Value = serde_json::from_str(&api.body).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 3);
+    assert_eq!(json[2]["epoch"], 4);
+    assert_eq!(json[2]["smeshers"], 1);
+    assert_eq!(json[2]["bytes"], 12u64 << 36);
+
+    let latest = get(&app, "/analytics/distribution").await;
+    assert!(latest.body.contains("Epoch 4"));
+    assert!(latest.body.contains("8–15 units"));
+
+    let api = get(&app, "/api/analytics/distribution?epoch=2").await;
+    let json: serde_json::Value = serde_json::from_str(&api.body).unwrap();
+    assert_eq!(
+        json["buckets"],
+        serde_json::json!([{ "min": 4, "max": 7, "smeshers": 1 }])
+    );
+    for epoch in [9, 0, i32::MIN] {
+        let uri = format!("/api/analytics/distribution?epoch={}", epoch);
+        assert_eq!(get(&app, &uri).await.status, StatusCode::NOT_FOUND);
+    }
+    let empty = get(&app, "/analytics/distribution?epoch=9").await;
+    assert!(empty.body.contains("No ATXs for this epoch."));
+}
+
+#[tokio::test]
+async fn activity_stats() {
+    let app = test_app().await;
+
+    let page = get(&app, "/stats").await;
+    assert_eq!(page.status, StatusCode::OK);
+    assert!(page.body.contains("hx-get=\"/stats/charts?period=epoch\""));
+    let days = get(&app, "/stats?period=day").await;
+    assert!(days.body.contains("hx-get=\"/stats/charts?period=day\""));
+
+    let charts = get(&app, "/stats/charts").await;
+    assert_golden("stats_charts.html", &charts.body);
+    assert!(charts.body.contains("2.5 SMH"));
+
+    let epochs = get(&app, "/api/stats").await;
+    let json: serde_json::Value = serde_json::from_str(&epochs.body).unwrap();
+    assert_eq!(json["period"], "epoch");
+    let activity = json["activity"].as_array().unwrap();
+    assert_eq!(activity.len(), 4);
+    // The vault spawned by the multisig account in layer 2 has another
+    // principal; the failed spend pays its fee but moves nothing.
+    assert_eq!(activity[0]["accounts"], 2);
+    assert_eq!(activity[0]["fees"], "600");
+    assert_eq!(activity[1]["transactions"], 8);
+    assert_eq!(activity[1]["transferred"], SPENT.to_string());
+    assert_eq!(activity[3]["gas"], 0);
+
+    let layers = get(&app, "/api/stats?period=layer&count=3").await;
+    let json: serde_json::Value = serde_json::from_str(&layers.body).unwrap();
+    let periods: Vec<_> = json["activity"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|activity| activity["period"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(periods, ["10", "11", "12"]);
+
+    let days = get(&app, "/api/stats?period=day&count=1000").await;
+    let json: serde_json::Value = serde_json::from_str(&days.body).unwrap();
+    let activity = json["activity"].as_array().unwrap();
+    assert_eq!(activity.len(), 50);
+    assert_eq!(activity[49]["period"], "2023-11-14");
+    assert_eq!(activity[49]["transactions"], 25);
+    assert_eq!(activity[48]["transactions"], 0);
+
+    let invalid = get(&app, "/api/stats?period=week").await;
+    assert_eq!(invalid.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn finished_periods_are_cached() {
+    let state = test_state(fixture().await).await;
+    let app = app(state.clone());
+    let transactions_of = |activity: &serde_json::Value| {
+        activity["activity"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|period| period["transactions"].as_u64().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let uri = format!("/api/stats?period=layer&count={}", LAST_LAYER + 1);
+    let before: serde_json::Value = serde_json::from_str(&get(&app, &uri).await.body).unwrap();
+
+    transactions::Entity::delete_many()
+        .filter(transactions::Column::Layer.lt(LAST_LAYER))
+        .exec(&state.database)
+        .await
+        .unwrap();
+    let after: serde_json::Value = serde_json::from_str(&get(&app, &uri).await.body).unwrap();
+
+    // Earlier layers are finished and not read again.
+    let (before, after) = (transactions_of(&before), transactions_of(&after));
+    assert_eq!(after, before);
+    assert!(before.iter().sum::<u64>() > 0);
+}
+
+#[tokio::test]
+async fn search_redirects() {
+    let app = test_app().await;
+
+    let layer = get(&app, "/search?input=5").await;
+    assert_eq!(layer.headers["HX-Redirect"], "/layer/5");
+
+    let account = get(&app, &format!("/search?input={}", ACCOUNT)).await;
+    assert_eq!(
+        account.headers["HX-Redirect"],
+        format!("/account/{}", ACCOUNT).as_str()
+    );
+
+    let bech32 = Address(address(ACCOUNT)).to_bech32("sm");
+    let account = get(&app, &format!("/search?input={}", bech32)).await;
+    assert_eq!(
+        account.headers["HX-Redirect"],
+        format!("/account/{}", ACCOUNT).as_str()
+    );
+    let other_network = Address(address(ACCOUNT)).to_bech32("stest");
+    let account = get(&app, &format!("/search?input={}", other_network)).await;
+    assert_eq!(account.headers["HX-Redirect"], "/");
+
+    let missing_layer = get(&app, "/search?input=999").await;
+    assert_eq!(missing_layer.headers["HX-Redirect"], "/");
+
+    let malformed = get(&app, "/search?input=xyz").await;
+    assert_eq!(malformed.status, StatusCode::OK);
+    assert_eq!(malformed.headers["HX-Redirect"], "/");
+
+    let missing = get(&app, "/search").await;
+    assert_eq!(missing.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn health_dashboard() {
+    let app = test_app().await;
+
+    let page = get(&app, "/health").await;
+    assert_eq!(page.status, StatusCode::OK);
+
+    let status = get(&app, "/health/status").await;
+    assert_eq!(status.status, StatusCode::OK);
+    assert!(status.body.contains(&format!("#{}", LAST_CERTIFIED_LAYER)));
+    assert!(status.body.contains("4 layers"));
+    assert!(status.body.contains("empty applied block"));
+    assert!(!status.body.contains("repeats previous layer"));
+    assert!(!status.body.contains("state changed without applied block"));
+}
+
+#[tokio::test]
+async fn health_detects_broken_hash_chain() {
+    let conn = fixture().await;
+
+    let mut repeated = layers::Entity::find_by_id(7)
+        .one(&conn)
+        .await
+        .unwrap()
+        .unwrap()
+        .into_active_model();
+    repeated.aggregated_hash = Set(Some(vec![0xa6; 32]));
+    repeated.update(&conn).await.unwrap();
+
+    let mut unexplained = layers::Entity::find_by_id(4)
+        .one(&conn)
+        .await
+        .unwrap()
+        .unwrap()
+        .into_active_model();
+    unexplained.applied_block = Set(None);
+    unexplained.update(&conn).await.unwrap();
+
+    let app = test_app_with(conn).await;
+    let status = get(&app, "/health/status").await;
+
+    assert!(status
+        .body
+        .contains("aggregated hash repeats previous layer"));
+    assert!(status.body.contains("state changed without applied block"));
+    assert!(status.body.contains("href=\"/layer/7\""));
+    assert!(status.body.contains("href=\"/layer/4\""));
+}
+
+#[tokio::test]
+async fn checkpoint_restore() {
+    let app = test_app().await;
+    assert_eq!(get(&app, "/checkpoint").await.body.trim(), "");
+    let missing = get(&app, "/layer/0/layer").await;
+    assert!(missing.body.contains("Layer 0 not found"));
+    let layers = get(&app, "/layers/list?limit=100").await;
+    assert!(!layers.body.contains("restored from a"));
+
+    let conn = fixture().await;
+    recovery::ActiveModel {
+        id: Set(1),
+        restore: Set(5),
+    }
+    .insert(&conn)
+    .await
+    .unwrap();
+    let app = test_app_with(conn).await;
+
+    let page = get(&app, "/").await;
+    assert!(page.body.contains("hx-get=\"/checkpoint\""));
+    let banner = get(&app, "/checkpoint").await;
+    assert!(banner.body.contains("restored from a checkpoint"));
+    assert!(banner.body.contains("href=\"/layer/5\""));
+    let info = get(&app, "/home/global_state").await;
+    assert!(info.body.contains("Restored from checkpoint at layer 5"));
+
+    // Only the last page explains where the history ends.
+    let first = get(&app, "/layers/list?limit=10").await;
+    assert!(!first.body.contains("History before layer 5"));
+    let last = get(&app, "/layers/list?limit=100").await;
+    assert!(last
+        .body
+        .contains("History before layer 5 is not available"));
+    let rewards = get(&app, &format!("/account/{}/rewards?limit=100", ACCOUNT)).await;
+    assert!(rewards.body.contains("History before layer 5"));
+
+    let before = get(&app, "/layer/0/layer").await;
+    assert_eq!(before.status, StatusCode::NOT_FOUND);
+    assert!(before
+        .body
+        .contains("checkpoint this node was restored from"));
+    let after = get(&app, "/layer/99/layer").await;
+    assert!(after.body.contains("Layer 99 not found"));
+}
+
+#[tokio::test]
+async fn probes_and_metrics() {
+    let state = test_state(fixture().await).await;
+    let tx_count = transactions::Entity::find()
+        .count(&state.database)
+        .await
+        .unwrap();
+    crate::metrics::update_chain_gauges(&state).await.unwrap();
+    let app = app(state);
+
+    let healthz = get(&app, "/healthz").await;
+    assert_eq!(healthz.status, StatusCode::OK);
+    assert_eq!(healthz.body, r#"{"status":"ok","checks":{}}"#);
+
+    let readyz = get(&app, "/readyz").await;
+    let report: serde_json::Value = serde_json::from_str(&readyz.body).unwrap();
+    assert_eq!(report["checks"]["database"]["status"], "ok");
+    assert_eq!(report["checks"]["global_state"]["status"], "ok");
+    assert!(report["checks"]["node"]["status"].is_string());
+
+    get(&app, "/layer/5/layer").await;
+
+    let metrics = get(&app, "/metrics").await;
+    assert_eq!(metrics.status, StatusCode::OK);
+    assert!(metrics.body.contains(
+        r#"smscan_http_requests_total{method="GET",route="/layer/:id/layer",status="200"}"#
+    ));
+    assert!(metrics
+        .body
+        .contains("smscan_db_query_duration_seconds_bucket"));
+    assert!(metrics.body.contains(&format!(
+        "smscan_transactions{{network=\"{}\"}} {}",
+        TEST_NETWORK, tx_count
+    )));
+    for epoch in 1..=3 {
+        assert!(metrics.body.contains(&format!(
+            "smscan_atxs{{network=\"{}\",epoch=\"{}\"}} 1",
+            TEST_NETWORK, epoch
+        )));
+    }
+}
+
+/// Runs against SQLite, and against the Postgres database at
+/// `TEST_POSTGRES_URL` when it is set.
+#[tokio::test]
+async fn total_supply_on_every_backend() {
+    let conn = fixture().await;
+    let accounts = accounts::Entity::find().all(&conn).await.unwrap();
+    let expected: i64 = accounts.iter().map(|account| account.balance).sum();
+    assert_eq!(
+        crate::metrics::total_supply(&conn).await.unwrap(),
+        crate::types::Amount::from(expected)
+    );
+
+    let Ok(url) = std::env::var("TEST_POSTGRES_URL") else {
+        return;
+    };
+    let postgres = Database::connect(url).await.unwrap();
+    let backend = postgres.get_database_backend();
+    postgres
+        .execute_unprepared("DROP TABLE IF EXISTS accounts")
+        .await
+        .unwrap();
+    let table = Schema::new(backend).create_table_from_entity(accounts::Entity);
+    postgres.execute(backend.build(&table)).await.unwrap();
+    insert::<accounts::ActiveModel, _>(&postgres, accounts).await;
+
+    assert_eq!(
+        crate::metrics::total_supply(&postgres).await.unwrap(),
+        crate::types::Amount::from(expected)
+    );
+}
+
+#[tokio::test]
+async fn networks_by_subdomain_and_prefix() {
+    let names = vec![TEST_NETWORK.to_string(), "testnet".to_string()];
+    let mut apps = vec![];
+    for name in &names {
+        let mut state = test_state(fixture().await).await;
+        state.network = std::sync::Arc::new(test_network(name));
+        state.networks = std::sync::Arc::new(names.clone());
+        apps.push((name.clone(), app(state)));
+    }
+    let app = networks_router(apps);
+
+    let current = |response: &TestResponse| {
+        let start = response.body.find("font-bold\">").unwrap() + "font-bold\">".len();
+        let end = response.body[start..].find('<').unwrap();
+        response.body[start..start + end].to_string()
+    };
+
+    let default = get(&app, "/networks").await;
+    assert!(default.body.contains("href=\"/net/testnet/\""));
+    assert_eq!(current(&default), TEST_NETWORK);
+
+    let subdomain = send(
+        &app,
+        Request::get("/networks")
+            .header("host", "testnet.smscan.test")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(current(&subdomain), "testnet");
+
+    // The path wins over the subdomain, and links keep the prefix.
+    let prefixed = send(
+        &app,
+        Request::get("/net/testnet/networks")
+            .header("host", "mainnet.smscan.test")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(current(&prefixed), "testnet");
+
+    let home = get(&app, "/net/testnet").await;
+    assert_eq!(home.status, StatusCode::OK);
+    assert!(home.body.contains("hx-get=\"/net/testnet/networks\""));
+    let layers = get(&app, "/net/testnet/layers/list?limit=5").await;
+    assert!(layers.body.contains("href=\"/net/testnet/layer/12\""));
+    assert!(layers.body.contains("hx-get=\"/net/testnet/layers/list?"));
+    assert!(!get(&app, "/layers/list?limit=5")
+        .await
+        .body
+        .contains("/net/"));
+
+    // Both serve testnet, but cached tabs keep the links they were rendered
+    // with.
+    let tab = get(&app, "/net/testnet/layer/5/transactions").await;
+    assert!(tab.body.contains("href=\"/net/testnet/"));
+    assert_eq!(tab.headers["vary"], "Accept-Encoding, Host");
+    let by_host = send(
+        &app,
+        Request::get("/layer/5/transactions")
+            .header("host", "testnet.smscan.test")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert!(!by_host.body.contains("/net/"));
+
+    let search = get(&app, "/net/testnet/search?input=5").await;
+    assert_eq!(search.headers["hx-redirect"], "/net/testnet/layer/5");
+
+    let unknown = get(&app, "/net/devnet/layers").await;
+    assert_eq!(unknown.status, StatusCode::NOT_FOUND);
+
+    let single = get(&test_app().await, "/networks").await;
+    assert_eq!(single.status, StatusCode::OK);
+    assert!(single.body.trim().is_empty());
+}
+
+#[tokio::test]
+async fn layer_responses_are_cached() {
+    let conn = fixture().await;
+    let app = test_app_with(conn.clone()).await;
+
+    let first = get(&app, "/layer/5/layer").await;
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(
+        first.headers["cache-control"],
+        "public, max-age=31536000, immutable"
+    );
+    assert_eq!(first.headers["vary"], "Accept-Encoding");
+    let etag = first.headers["etag"].clone();
+    assert!(etag.to_str().unwrap().starts_with("W/\""));
+
+    // The page links to the previous and next layers, which may change.
+    let page = get(&app, "/layer/5").await;
+    assert_eq!(page.status, StatusCode::OK);
+    assert!(page.headers.get("etag").is_none());
+    assert!(page.headers.get("cache-control").is_none());
+
+    // Served from the cache even once the layer is gone from the database.
+    layers::Entity::delete_by_id(5).exec(&conn).await.unwrap();
+    let cached = get(&app, "/layer/5/layer").await;
+    assert_eq!(cached.status, StatusCode::OK);
+    assert_eq!(cached.headers["etag"], etag);
+    assert_eq!(cached.body, first.body);
+
+    let revalidated = send(
+        &app,
+        Request::get("/layer/5/layer")
+            .header("if-none-match", etag)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(revalidated.status, StatusCode::NOT_MODIFIED);
+    assert!(revalidated.body.is_empty());
+
+    let recent = get(&app, &format!("/layer/{}/layer", LAST_LAYER)).await;
+    assert_eq!(recent.headers["cache-control"], "public, max-age=10");
+
+    let missing = get(&app, "/layer/99/layer").await;
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+    assert!(missing.headers.get("etag").is_none());
+}
+
+#[tokio::test]
+async fn rate_limits_per_client() {
+    use crate::limits::{Limiter, RouteGroup};
+    use std::{sync::Arc, time::Duration};
+
+    let mut state = test_state(fixture().await).await;
+    state.limiters.search = Arc::new(
+        Limiter::new(RouteGroup::Search, 2, Duration::from_secs(5)).trust_forwarded_for(true),
+    );
+    state.limiters.api = Arc::new(
+        Limiter::new(RouteGroup::Api, 1, Duration::from_secs(5)).trust_forwarded_for(true),
+    );
+    let app = app(state);
+
+    let from = |uri: &str, client: &str| {
+        Request::get(uri)
+            .header("x-forwarded-for", format!("203.0.113.9, {}", client))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    for _ in 0..2 {
+        let search = send(&app, from("/search?input=5", "198.51.100.1")).await;
+        assert_eq!(search.status, StatusCode::OK);
+    }
+    let limited = send(&app, from("/search?input=5", "198.51.100.1")).await;
+    assert_eq!(limited.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(limited.headers["retry-after"], "30");
+    assert!(limited
+        .body
+        .contains("Too many requests, please retry in 30s."));
+
+    let other = send(&app, from("/search?input=5", "198.51.100.2")).await;
+    assert_eq!(other.status, StatusCode::OK);
+    let page = send(&app, from("/layer/5/layer", "198.51.100.1")).await;
+    assert_eq!(page.status, StatusCode::OK);
+
+    send(&app, from("/api/stats", "198.51.100.1")).await;
+    let api = send(&app, from("/api/stats", "198.51.100.1")).await;
+    assert_eq!(api.status, StatusCode::TOO_MANY_REQUESTS);
+    let error: serde_json::Value = serde_json::from_str(&api.body).unwrap();
+    assert_eq!(error["error"], "Too many requests, please retry in 60s.");
+
+    // Without a known client address the limit cannot apply.
+    for _ in 0..3 {
+        assert_eq!(get(&app, "/search?input=5").await.status, StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn degraded_mode_banner() {
+    let conn = fixture().await;
+    let state = test_state(conn.clone()).await;
+    let app = app(state.clone());
+
+    let synced = get(&app, "/sync").await;
+    assert_eq!(synced.status, StatusCode::OK);
+    assert!(synced.body.trim().is_empty());
+
+    state.global_state.write().await.current_layer = 100;
+    let syncing = get(&app, "/sync").await;
+    assert!(syncing
+        .body
+        .contains("processed layer <span class=\"font-bold\">10</span>"));
+    assert!(syncing
+        .body
+        .contains("of <span class=\"font-bold\">100</span>"));
+
+    conn.execute_unprepared("DROP TABLE layers").await.unwrap();
+    let unavailable = get(&app, "/sync").await;
+    assert_eq!(unavailable.status, StatusCode::OK);
+    assert!(unavailable
+        .body
+        .contains("The node database is not available yet"));
+
+    let page = get(&app, "/").await;
+    assert!(page.body.contains("hx-get=\"/sync\""));
+}
+
+#[cfg(not(feature = "dev"))]
+#[tokio::test]
+async fn production_headers_and_assets() {
+    let app = crate::mode::layers(test_app().await);
+
+    let page = get(&app, "/").await;
+    assert_eq!(page.headers["x-content-type-options"], "nosniff");
+    assert_eq!(page.headers["x-frame-options"], "DENY");
+    assert!(page.body.contains(&format!(
+        "/assets/main.css?v={}",
+        crate::mode::ASSETS_VERSION
+    )));
+
+    let compressed = send(
+        &app,
+        Request::get("/layers")
+            .header("accept-encoding", "gzip")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(compressed.headers["content-encoding"], "gzip");
+
+    // Cached layer tabs are compressed on the way out, under a weak ETag.
+    let tab = send(
+        &app,
+        Request::get("/layer/5/layer")
+            .header("accept-encoding", "gzip")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(tab.headers["content-encoding"], "gzip");
+    let vary: Vec<_> = tab.headers.get_all("vary").iter().collect();
+    assert_eq!(vary, ["Accept-Encoding"], "{:?}", vary);
+
+    let css = send(
+        &app,
+        Request::get("/assets/main.css")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(css.status, StatusCode::OK);
+    assert_eq!(
+        css.headers["cache-control"],
+        "public, max-age=31536000, immutable"
+    );
+    assert!(css.headers.get("x-content-type-options").is_some());
+}
+
+/// A stand-in for `grpcurl` answering every node method the global state
+/// needs, so it can be refreshed without a node.
+#[cfg(unix)]
+fn fake_grpcurl() -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let id = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, [0xe3; 32]);
+    let script = format!(
+        r#"#!/bin/sh
+case "$3" in
+  *ActivationService.Highest) echo '{{"atx":{{"id":{{"id":"{id}"}},"layer":{{"number":12}},"coinbase":{{"address":"sm1"}},"prevAtx":{{"id":"{id}"}}}}}}' ;;
+  *GenesisTime) echo '{{"unixtime":{{"value":"1689310800"}}}}' ;;
+  *CurrentLayer) echo '{{"layernum":{{"number":14}}}}' ;;
+  *CurrentEpoch) echo '{{"epochnum":{{"number":3}}}}' ;;
+  *EpochNumLayers) echo '{{"numlayers":{{"number":4}}}}' ;;
+  *LayerDuration) echo '{{"duration":{{"value":"300"}}}}' ;;
+esac
+"#
+    );
+
+    let path = std::env::temp_dir().join(format!("smscan-grpcurl-{}", std::process::id()));
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn readiness_follows_background_refresh() {
+    let node = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let mut state = test_state(fixture().await).await;
+    state.network = std::sync::Arc::new(Network {
+        node_host: Some(node.local_addr().unwrap().to_string()),
+        grpcurl: fake_grpcurl(),
+        ..test_network(TEST_NETWORK)
+    });
+    *state.global_state.write().await = GlobalState::loading();
+    let app = app(state.clone());
+
+    let readyz = get(&app, "/readyz").await;
+    assert_eq!(readyz.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(readyz.body.contains("never fetched"));
+
+    crate::components::global_state::refresh_global_state_every(
+        state.clone(),
+        std::time::Duration::from_millis(50),
+    );
+    let mut ready = false;
+    for _ in 0..100 {
+        if get(&app, "/readyz").await.status == StatusCode::OK {
+            ready = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    assert!(ready, "readyz never turned ready");
+    assert_eq!(state.global_state.read().await.current_layer, 14);
+}
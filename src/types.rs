@@ -0,0 +1,477 @@
+//! Typed identifiers and amounts used by the view models and handlers.
+//!
+//! Byte ids render as lowercase hex and parse back from it, checking the
+//! expected length. All types convert to and from sea-orm values so they can
+//! be used directly in filters and custom query results.
+
+use sea_orm::{
+    sea_query::{ArrayType, ColumnType, Nullable, ValueType, ValueTypeErr},
+    ColIdx, DeriveValueType, QueryResult, TryGetError, TryGetable, Value,
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// Number of trailing bytes kept by `short()`.
+const SHORT_BYTES: usize = 6;
+
+macro_rules! int_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(
+            Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord,
+            Serialize, Deserialize, DeriveValueType,
+        )]
+        #[serde(transparent)]
+        pub struct $name(pub i32);
+
+        impl From<i32> for $name {
+            fn from(id: i32) -> Self {
+                $name(id)
+            }
+        }
+
+        impl From<$name> for i32 {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = std::num::ParseIntError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse().map($name)
+            }
+        }
+    };
+}
+
+macro_rules! hex_id {
+    ($(#[$meta:meta])* $name:ident, $len:expr) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, DeriveValueType)]
+        pub struct $name(pub Vec<u8>);
+
+        impl $name {
+            pub const LEN: usize = $len;
+
+            pub fn as_bytes(&self) -> &[u8] {
+                &self.0
+            }
+
+            /// Hex of the last few bytes, for compact lists.
+            pub fn short(&self) -> String {
+                hex::encode(&self.0[self.0.len().saturating_sub(SHORT_BYTES)..])
+            }
+        }
+
+        impl From<Vec<u8>> for $name {
+            fn from(bytes: Vec<u8>) -> Self {
+                $name(bytes)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&hex::encode(&self.0))
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = hex::FromHexError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let bytes = hex::decode(s)?;
+                if bytes.len() != Self::LEN {
+                    return Err(hex::FromHexError::InvalidStringLength);
+                }
+                Ok($name(bytes))
+            }
+        }
+
+        impl Nullable for $name {
+            fn null() -> Value {
+                Value::Bytes(None)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.to_string())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer)?
+                    .parse()
+                    .map_err(de::Error::custom)
+            }
+        }
+    };
+}
+
+int_id!(
+    /// Layer number.
+    LayerId
+);
+
+int_id!(
+    /// Epoch number.
+    EpochId
+);
+
+impl EpochId {
+    /// The epoch before this one, `None` for the first epoch.
+    pub fn previous(self) -> Option<EpochId> {
+        (self.0 > 0).then(|| EpochId(self.0 - 1))
+    }
+
+    /// First and last layer of the epoch, `None` when they are out of range.
+    pub fn layers(self, epoch_num_layers: i32) -> Option<(LayerId, LayerId)> {
+        if self.0 < 0 || epoch_num_layers <= 0 {
+            return None;
+        }
+        let first = self.0.checked_mul(epoch_num_layers)?;
+        let last = first.checked_add(epoch_num_layers - 1)?;
+        Some((LayerId(first), LayerId(last)))
+    }
+}
+
+hex_id!(
+    /// Transaction id.
+    TxId,
+    32
+);
+
+hex_id!(
+    /// Activation transaction id.
+    AtxId,
+    32
+);
+
+hex_id!(
+    /// Smesher identity, the public key signing ATXs and ballots.
+    NodeId,
+    32
+);
+
+hex_id!(
+    /// Account address.
+    Address,
+    24
+);
+
+hex_id!(
+    /// Ed25519 public key of an account signer.
+    PublicKey,
+    32
+);
+
+hex_id!(
+    /// Block id.
+    BlockId,
+    20
+);
+
+hex_id!(
+    /// Random beacon the network agreed on for an epoch.
+    Beacon,
+    4
+);
+
+hex_id!(
+    /// Proposal id.
+    ProposalId,
+    20
+);
+
+hex_id!(
+    /// Aggregated or state hash of a layer.
+    Hash32,
+    32
+);
+
+hex_id!(
+    /// Hash of a PoET proof, which ATXs reference to prove elapsed time.
+    PoetRef,
+    32
+);
+
+const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+fn bech32_polymod(values: impl IntoIterator<Item = u8>) -> u32 {
+    const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    values.into_iter().fold(1, |checksum, value| {
+        let top = checksum >> 25;
+        let checksum = ((checksum & 0x1ffffff) << 5) ^ value as u32;
+        (0..5)
+            .filter(|bit| (top >> bit) & 1 == 1)
+            .fold(checksum, |checksum, bit| checksum ^ GENERATOR[bit])
+    })
+}
+
+fn bech32_hrp_expand(hrp: &str) -> impl Iterator<Item = u8> + '_ {
+    hrp.bytes()
+        .map(|c| c >> 5)
+        .chain([0])
+        .chain(hrp.bytes().map(|c| c & 31))
+}
+
+/// Regroups bits, padding the last group when encoding. Decoding rejects
+/// leftover bits that are not zero padding.
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc = 0u32;
+    let mut bits = 0;
+    let mut out = vec![];
+    for &value in data {
+        acc = (acc << from) | value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & ((1 << to) - 1)) as u8);
+        }
+    }
+    if pad && bits > 0 {
+        out.push(((acc << (to - bits)) & ((1 << to) - 1)) as u8);
+    } else if !pad && (bits >= from || (acc << (to - bits)) & ((1 << to) - 1) != 0) {
+        return None;
+    }
+    Some(out)
+}
+
+/// Bech32 (BIP-173) string of `bytes` with the human readable part `hrp`.
+pub fn bech32_encode(hrp: &str, bytes: &[u8]) -> String {
+    let data = convert_bits(bytes, 8, 5, true).unwrap_or_default();
+    let polymod = bech32_polymod(
+        bech32_hrp_expand(hrp)
+            .chain(data.iter().copied())
+            .chain([0; 6]),
+    ) ^ 1;
+    let checksum = (0..6).map(|i| ((polymod >> (5 * (5 - i))) & 31) as u8);
+
+    let mut encoded = format!("{}1", hrp);
+    encoded.extend(
+        data.iter()
+            .copied()
+            .chain(checksum)
+            .map(|value| BECH32_CHARSET[value as usize] as char),
+    );
+    encoded
+}
+
+/// Human readable part and bytes of a bech32 string, `None` when it is
+/// malformed or its checksum does not match.
+pub fn bech32_decode(encoded: &str) -> Option<(String, Vec<u8>)> {
+    if encoded.chars().any(|c| c.is_ascii_uppercase())
+        && encoded.chars().any(|c| c.is_ascii_lowercase())
+    {
+        return None;
+    }
+    let encoded = encoded.to_lowercase();
+    let (hrp, data) = encoded.rsplit_once('1')?;
+    if hrp.is_empty() || data.len() < 6 {
+        return None;
+    }
+    let data = data
+        .bytes()
+        .map(|c| BECH32_CHARSET.iter().position(|&d| d == c).map(|i| i as u8))
+        .collect::<Option<Vec<u8>>>()?;
+    if bech32_polymod(bech32_hrp_expand(hrp).chain(data.iter().copied())) != 1 {
+        return None;
+    }
+    let bytes = convert_bits(&data[..data.len() - 6], 5, 8, false)?;
+    Some((hrp.to_string(), bytes))
+}
+
+impl Address {
+    /// The address as wallets show it on the network using `hrp`.
+    pub fn to_bech32(&self, hrp: &str) -> String {
+        bech32_encode(hrp, &self.0)
+    }
+
+    pub fn from_bech32(encoded: &str, hrp: &str) -> Option<Self> {
+        let (prefix, bytes) = bech32_decode(encoded)?;
+        (prefix == hrp && bytes.len() == Self::LEN).then_some(Address(bytes))
+    }
+}
+
+pub const SMIDGE_PER_SMH: u64 = 1_000_000_000;
+
+/// An exact amount of smidge, displayed in SMH.
+///
+/// Serialized as a decimal string of smidge so large balances survive JSON
+/// clients that only have doubles.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Amount(pub u64);
+
+impl Amount {
+    pub fn smidge(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Negative database values can only come from corruption and are clamped
+/// to zero.
+impl From<i64> for Amount {
+    fn from(smidge: i64) -> Self {
+        Amount(smidge.try_into().unwrap_or(0))
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let whole = self.0 / SMIDGE_PER_SMH;
+        let fraction = self.0 % SMIDGE_PER_SMH;
+
+        if fraction == 0 {
+            write!(f, "{}", whole)
+        } else {
+            let fraction = format!("{:09}", fraction);
+            write!(f, "{}.{}", whole, fraction.trim_end_matches('0'))
+        }
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map(Amount)
+            .map_err(de::Error::custom)
+    }
+}
+
+impl From<Amount> for Value {
+    fn from(amount: Amount) -> Self {
+        Value::BigInt(Some(amount.0.try_into().unwrap_or(i64::MAX)))
+    }
+}
+
+impl TryGetable for Amount {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, idx: I) -> Result<Self, TryGetError> {
+        i64::try_get_by(res, idx).map(Amount::from)
+    }
+}
+
+impl ValueType for Amount {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        <i64 as ValueType>::try_from(v).map(Amount::from)
+    }
+
+    fn type_name() -> String {
+        "Amount".to_owned()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::BigInt
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::BigInteger
+    }
+}
+
+impl Nullable for Amount {
+    fn null() -> Value {
+        Value::BigInt(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amount_displays_exact_smh() {
+        assert_eq!(Amount(0).to_string(), "0");
+        assert_eq!(Amount(SMIDGE_PER_SMH).to_string(), "1");
+        assert_eq!(Amount(5_500_000_005).to_string(), "5.500000005");
+        assert_eq!(Amount(1_200).to_string(), "0.0000012");
+        assert_eq!(Amount(u64::MAX).to_string(), "18446744073.709551615");
+        assert_eq!(Amount::from(-1), Amount(0));
+    }
+
+    #[test]
+    fn amount_serializes_as_smidge_string() {
+        let json = serde_json::to_string(&Amount(5_500_000_005)).unwrap();
+        assert_eq!(json, "\"5500000005\"");
+        assert_eq!(
+            serde_json::from_str::<Amount>(&json).unwrap(),
+            Amount(5_500_000_005)
+        );
+    }
+
+    #[test]
+    fn epochs_stay_in_range() {
+        assert_eq!(EpochId(3).previous(), Some(EpochId(2)));
+        assert_eq!(EpochId(0).previous(), None);
+        assert_eq!(EpochId(i32::MIN).previous(), None);
+
+        assert_eq!(EpochId(3).layers(4), Some((LayerId(12), LayerId(15))));
+        assert_eq!(EpochId(3).layers(0), None);
+        assert_eq!(EpochId(-1).layers(4), None);
+        assert_eq!(EpochId(i32::MAX).layers(4), None);
+        assert_eq!(
+            EpochId(i32::MAX / 4).layers(4),
+            Some((LayerId(i32::MAX - 3), LayerId(i32::MAX)))
+        );
+        assert_eq!(EpochId(i32::MAX / 4 + 1).layers(4), None);
+    }
+
+    #[test]
+    fn hex_ids_check_length() {
+        let address = "00000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+
+        assert_eq!(address.parse::<Address>().unwrap().to_string(), address);
+        assert_eq!(
+            address.parse::<TxId>(),
+            Err(hex::FromHexError::InvalidStringLength)
+        );
+        assert!("zz".parse::<Address>().is_err());
+        assert_eq!(address.parse::<Address>().unwrap().short(), "aaaaaaaaaaaa");
+        assert_eq!(Address(vec![1, 2]).short(), "0102");
+        assert_eq!(Hash32::default().short(), "");
+    }
+
+    #[test]
+    fn addresses_round_trip_through_bech32() {
+        assert_eq!(bech32_decode("a12uel5l"), Some(("a".to_string(), vec![])));
+        assert_eq!(bech32_encode("a", &[]), "a12uel5l");
+        assert!(bech32_decode("a12uel5m").is_none());
+        assert!(bech32_decode("A12UEL5L").is_some());
+        assert!(bech32_decode("A12uEl5l").is_none());
+
+        let address = Address(vec![0xaa; 24]);
+        let encoded = address.to_bech32("sm");
+        assert!(encoded.starts_with("sm1"));
+        assert_eq!(Address::from_bech32(&encoded, "sm"), Some(address));
+        assert_eq!(Address::from_bech32(&encoded, "stest"), None);
+        assert_eq!(
+            Address::from_bech32(&bech32_encode("sm", &[0xaa; 20]), "sm"),
+            None
+        );
+    }
+
+    #[test]
+    fn hex_ids_round_trip_through_json() {
+        let id = TxId(vec![0xab; 32]);
+        let json = serde_json::to_string(&id).unwrap();
+
+        assert_eq!(json, format!("\"{}\"", "ab".repeat(32)));
+        assert_eq!(serde_json::from_str::<TxId>(&json).unwrap(), id);
+        assert_eq!(serde_json::to_string(&LayerId(7)).unwrap(), "7");
+    }
+}
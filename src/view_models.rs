@@ -0,0 +1,624 @@
+//! Presentation models shared by the HTML fragments and the JSON API.
+//!
+//! Every conversion from a `db_entities` model is total: missing or malformed
+//! blobs render as empty values instead of panicking.
+
+use crate::{
+    db_entities::{
+        accounts, atxs, beacons, blocks, identities, layers, poets, rewards, transactions,
+    },
+    types::{
+        Address, Amount, AtxId, Beacon, BlockId, EpochId, Hash32, LayerId, NodeId, ProposalId, TxId,
+    },
+    vm::{AccountState, Call, MalfeasanceProof, PoetProof, RawTx, Template, TxHeader, TxResult},
+};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+fn to_hex(bytes: &Option<Vec<u8>>) -> String {
+    bytes.as_deref().map(hex::encode).unwrap_or_default()
+}
+
+fn to_id<T: From<Vec<u8>> + Default>(bytes: Option<Vec<u8>>) -> T {
+    bytes.map(T::from).unwrap_or_default()
+}
+
+/// Decodes a big-endian integer stored in a blob of up to 8 bytes. Longer
+/// blobs only use their first 8 bytes.
+fn be_u64(bytes: &[u8]) -> u64 {
+    let bytes = &bytes[..bytes.len().min(8)];
+    let mut buffer = [0u8; 8];
+    buffer[8 - bytes.len()..].copy_from_slice(bytes);
+    u64::from_be_bytes(buffer)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Layer {
+    pub id: LayerId,
+    pub processed: bool,
+    pub hash: Hash32,
+    pub state_hash: Hash32,
+    pub applied_block: BlockId,
+}
+
+impl From<layers::Model> for Layer {
+    fn from(layer: layers::Model) -> Self {
+        Layer {
+            id: LayerId(layer.id),
+            processed: layer.processed.unwrap_or(0) == 1,
+            hash: to_id(layer.aggregated_hash),
+            state_hash: to_id(layer.state_hash),
+            applied_block: to_id(layer.applied_block),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Block {
+    pub id: BlockId,
+    pub layer: LayerId,
+    pub validity: bool,
+    pub block_data: String,
+}
+
+impl From<blocks::Model> for Block {
+    fn from(block: blocks::Model) -> Self {
+        Block {
+            id: BlockId(block.id),
+            layer: LayerId(block.layer),
+            validity: block.validity.unwrap_or(0) == 1,
+            block_data: to_hex(&block.block),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Transaction {
+    pub id: TxId,
+    pub tx: String,
+    pub header: String,
+    pub result: String,
+    pub layer: Option<LayerId>,
+    pub block: BlockId,
+    pub principal: Address,
+    pub nonce: Option<u64>,
+    pub timestamp: String,
+}
+
+impl Transaction {
+    pub fn nonce_label(&self) -> String {
+        self.nonce
+            .map(|nonce| nonce.to_string())
+            .unwrap_or_else(|| "-".to_string())
+    }
+}
+
+impl From<transactions::Model> for Transaction {
+    fn from(tx: transactions::Model) -> Self {
+        Transaction {
+            id: TxId(tx.id),
+            tx: to_hex(&tx.tx),
+            header: to_hex(&tx.header),
+            result: to_hex(&tx.result),
+            layer: tx.layer.map(LayerId),
+            block: to_id(tx.block),
+            principal: to_id(tx.principal),
+            nonce: tx.nonce.as_deref().map(be_u64),
+            timestamp: NaiveDateTime::from_timestamp_millis(tx.timestamp / 1_000_000)
+                .map(|timestamp| timestamp.to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// A transaction with its blobs decoded, for the transaction page.
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionDetails {
+    #[serde(flatten)]
+    pub tx: Transaction,
+    pub raw: Option<RawTx>,
+    pub decoded_header: Option<TxHeader>,
+    pub decoded_result: Option<TxResult>,
+}
+
+impl From<transactions::Model> for TransactionDetails {
+    fn from(tx: transactions::Model) -> Self {
+        TransactionDetails {
+            raw: tx.tx.as_deref().and_then(RawTx::decode),
+            decoded_header: tx.header.as_deref().and_then(TxHeader::decode),
+            decoded_result: tx.result.as_deref().and_then(TxResult::decode),
+            tx: Transaction::from(tx),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProposalInclusion {
+    pub proposal: ProposalId,
+    pub layer: LayerId,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockInclusion {
+    pub block: BlockId,
+    pub layer: LayerId,
+    /// Whether the block is the one the layer applied, rather than a block
+    /// hare did not agree on.
+    pub applied: bool,
+}
+
+/// The life of a transaction: received by the node, put in proposals,
+/// included in a block and executed when that block was applied.
+#[derive(Debug, Clone, Serialize)]
+pub struct Timeline {
+    pub received: String,
+    pub proposals: Vec<ProposalInclusion>,
+    pub blocks: Vec<BlockInclusion>,
+    pub result: Option<TxResult>,
+}
+
+impl Timeline {
+    pub fn first_proposal(&self) -> Option<LayerId> {
+        self.proposals.iter().map(|proposal| proposal.layer).min()
+    }
+
+    pub fn applied_block(&self) -> Option<&BlockInclusion> {
+        self.blocks.iter().find(|block| block.applied)
+    }
+}
+
+/// A transaction the node holds that is not in a layer yet.
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingTx {
+    pub id: TxId,
+    pub principal: Address,
+    pub nonce: Option<u64>,
+    pub gas_price: Option<u64>,
+    pub max_fee: Option<Amount>,
+    pub received: String,
+    pub age_seconds: i64,
+}
+
+impl PendingTx {
+    pub fn new(tx: transactions::Model, now: NaiveDateTime) -> Self {
+        let header = tx.header.as_deref().and_then(TxHeader::decode);
+        let gas_price = header.as_ref().map(|header| header.gas_price).or_else(|| {
+            tx.tx
+                .as_deref()
+                .and_then(RawTx::decode)
+                .map(|raw| raw.gas_price)
+        });
+        let received = NaiveDateTime::from_timestamp_millis(tx.timestamp / 1_000_000);
+
+        PendingTx {
+            id: TxId(tx.id),
+            principal: to_id(tx.principal),
+            nonce: tx.nonce.as_deref().map(be_u64),
+            gas_price,
+            max_fee: header.as_ref().map(TxHeader::max_fee),
+            received: received
+                .map(|received| received.to_string())
+                .unwrap_or_default(),
+            age_seconds: received
+                .map(|received| (now - received).num_seconds().max(0))
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn age_label(&self) -> String {
+        match self.age_seconds {
+            0..=59 => format!("{}s", self.age_seconds),
+            60..=3599 => format!("{}m", self.age_seconds / 60),
+            3600..=86399 => format!("{}h", self.age_seconds / 3600),
+            _ => format!("{}d", self.age_seconds / 86400),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Reward {
+    pub coinbase: Address,
+    pub layer: LayerId,
+    pub total_reward: Amount,
+    pub layer_reward: Amount,
+}
+
+impl From<rewards::Model> for Reward {
+    fn from(reward: rewards::Model) -> Self {
+        Reward {
+            coinbase: Address(reward.coinbase),
+            layer: LayerId(reward.layer),
+            total_reward: reward.total_reward.map(Amount::from).unwrap_or_default(),
+            layer_reward: reward.layer_reward.map(Amount::from).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Account {
+    pub address: Address,
+    pub balance: Amount,
+    pub next_nonce: i64,
+    pub layer_updated: LayerId,
+    pub template: String,
+    pub template_kind: Option<Template>,
+    pub state: String,
+    pub decoded_state: Option<AccountState>,
+}
+
+impl From<accounts::Model> for Account {
+    fn from(account: accounts::Model) -> Self {
+        let template_kind = account.template.as_deref().and_then(Template::from_address);
+        let decoded_state = template_kind
+            .zip(account.state.as_deref())
+            .and_then(|(template, state)| AccountState::decode(template, state));
+
+        Account {
+            address: Address(account.address),
+            balance: Amount::from(account.balance),
+            next_nonce: account.next_nonce,
+            layer_updated: LayerId(account.layer_updated as i32),
+            template: to_hex(&account.template),
+            template_kind,
+            state: to_hex(&account.state),
+            decoded_state,
+        }
+    }
+}
+
+/// How and when an account came to exist.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Creation {
+    pub first_seen: Option<LayerId>,
+    pub spawn: Option<Spawn>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Spawn {
+    pub tx: TxId,
+    pub layer: Option<LayerId>,
+    pub template: Template,
+    pub principal: Address,
+    pub self_spawn: bool,
+}
+
+impl Spawn {
+    /// Returns the spawn of `address` if `tx` is one.
+    pub fn from_tx(address: &Address, tx: transactions::Model, raw: RawTx) -> Option<Self> {
+        let Call::Spawn { template, .. } = raw.call else {
+            return None;
+        };
+
+        Some(Spawn {
+            tx: TxId(tx.id),
+            layer: tx.layer.map(LayerId),
+            template,
+            self_spawn: raw.principal == *address,
+            principal: raw.principal,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Atx {
+    pub id: AtxId,
+    pub epoch: EpochId,
+    pub effective_num_units: i32,
+    pub pubkey: NodeId,
+    pub coinbase: Address,
+    pub commitment_atx: Option<AtxId>,
+    pub sequence: Option<i64>,
+    pub base_tick_height: Option<i64>,
+    pub tick_count: Option<i64>,
+}
+
+impl Atx {
+    /// ATXs published in an epoch make their smesher eligible in the next.
+    pub fn target_epoch(&self) -> EpochId {
+        EpochId(self.epoch.0 + 1)
+    }
+
+    /// Weight in the target epoch's active set: effective units times the
+    /// ticks of the PoET proof.
+    pub fn weight(&self) -> u64 {
+        self.effective_num_units.max(0) as u64 * self.tick_count.unwrap_or(0).max(0) as u64
+    }
+}
+
+impl From<atxs::Model> for Atx {
+    fn from(atx: atxs::Model) -> Self {
+        Atx {
+            id: AtxId(atx.id),
+            epoch: EpochId(atx.epoch),
+            effective_num_units: atx.effective_num_units,
+            pubkey: to_id(atx.pubkey),
+            coinbase: to_id(atx.coinbase),
+            commitment_atx: atx.commitment_atx.map(AtxId),
+            sequence: atx.sequence,
+            base_tick_height: atx.base_tick_height,
+            tick_count: atx.tick_count,
+        }
+    }
+}
+
+/// An epoch's beacon, `None` when the node never agreed on one.
+#[derive(Debug, Clone, Serialize)]
+pub struct EpochBeacon {
+    pub epoch: EpochId,
+    pub beacon: Option<Beacon>,
+}
+
+impl From<beacons::Model> for EpochBeacon {
+    fn from(beacon: beacons::Model) -> Self {
+        EpochBeacon {
+            epoch: EpochId(beacon.epoch),
+            beacon: beacon.beacon.map(Beacon),
+        }
+    }
+}
+
+/// A PoET round and the proof its service published, if the node has it.
+#[derive(Debug, Clone, Serialize)]
+pub struct PoetRound {
+    #[serde(rename = "ref")]
+    pub proof_ref: String,
+    pub service_id: String,
+    pub round_id: String,
+    pub size: usize,
+    pub proof: Option<PoetProof>,
+}
+
+impl PoetRound {
+    /// Round ids are decimal counters; unparsable ones sort first.
+    pub fn round_number(&self) -> Option<u64> {
+        self.round_id.parse().ok()
+    }
+}
+
+impl From<poets::Model> for PoetRound {
+    fn from(poet: poets::Model) -> Self {
+        let proof = poet.poet.as_deref().and_then(PoetProof::decode);
+        let from_proof = |field: fn(&PoetProof) -> &String| {
+            proof.as_ref().map(field).cloned().unwrap_or_default()
+        };
+        PoetRound {
+            service_id: poet
+                .service_id
+                .unwrap_or_else(|| from_proof(|proof| &proof.service_id)),
+            round_id: poet
+                .round_id
+                .unwrap_or_else(|| from_proof(|proof| &proof.round_id)),
+            size: poet.poet.as_ref().map(Vec::len).unwrap_or(0),
+            proof_ref: poet.r#ref,
+            proof,
+        }
+    }
+}
+
+/// A smesher the node holds a malfeasance proof against.
+#[derive(Debug, Clone, Serialize)]
+pub struct MaliciousIdentity {
+    pub smesher: String,
+    pub received: String,
+    pub proof: Option<MalfeasanceProof>,
+}
+
+impl From<identities::Model> for MaliciousIdentity {
+    fn from(identity: identities::Model) -> Self {
+        MaliciousIdentity {
+            smesher: identity.pubkey.to_lowercase(),
+            received: NaiveDateTime::from_timestamp_opt(identity.received.into(), 0)
+                .map(|received| received.to_string())
+                .unwrap_or_default(),
+            proof: identity.proof.as_deref().and_then(MalfeasanceProof::decode),
+        }
+    }
+}
+
+/// Bytes of PoST data behind one space unit on mainnet.
+pub const BYTES_PER_UNIT: u64 = 64 << 30;
+const BYTES_PER_TIB: f64 = (1u64 << 40) as f64;
+
+/// Storage committed to the network in an epoch, counted from the ATXs
+/// published in the epoch before, which made their smeshers eligible.
+#[derive(Debug, Clone, Serialize)]
+pub struct EpochNetwork {
+    pub epoch: EpochId,
+    pub atxs: u64,
+    pub smeshers: u64,
+    pub units: u64,
+    pub bytes: u64,
+    pub average_units: f64,
+}
+
+impl EpochNetwork {
+    pub fn new(epoch: EpochId, atxs: u64, smeshers: u64, units: u64) -> Self {
+        EpochNetwork {
+            epoch,
+            atxs,
+            smeshers,
+            units,
+            bytes: units.saturating_mul(BYTES_PER_UNIT),
+            average_units: if smeshers > 0 {
+                units as f64 / smeshers as f64
+            } else {
+                0.0
+            },
+        }
+    }
+
+    pub fn tib(&self) -> f64 {
+        self.bytes as f64 / BYTES_PER_TIB
+    }
+}
+
+/// Smeshers committing between `min` and `max` units, both inclusive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnitsBucket {
+    pub min: u64,
+    pub max: u64,
+    pub smeshers: u64,
+}
+
+/// How many units smeshers committed in an epoch, in power of two buckets.
+#[derive(Debug, Clone, Serialize)]
+pub struct UnitsDistribution {
+    pub epoch: EpochId,
+    pub buckets: Vec<UnitsBucket>,
+}
+
+impl UnitsDistribution {
+    pub fn new(epoch: EpochId, units: impl IntoIterator<Item = u64>) -> Self {
+        let mut counts = BTreeMap::new();
+        for units in units {
+            *counts.entry(units.max(1).ilog2()).or_insert(0) += 1;
+        }
+        let buckets = match (counts.keys().next(), counts.keys().last()) {
+            (Some(&first), Some(&last)) => (first..=last)
+                .map(|bit| UnitsBucket {
+                    min: 1 << bit,
+                    max: (1 << bit) * 2 - 1,
+                    smeshers: counts.get(&bit).copied().unwrap_or(0),
+                })
+                .collect(),
+            _ => vec![],
+        };
+        UnitsDistribution { epoch, buckets }
+    }
+}
+
+/// Chain activity of one layer, epoch or day, from executed transactions.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Activity {
+    /// Layer or epoch number, or the `YYYY-MM-DD` day the node received them.
+    pub period: String,
+    pub transactions: u64,
+    /// Distinct principals that sent transactions.
+    pub accounts: u64,
+    /// Moved by successful spends and vault drains.
+    pub transferred: Amount,
+    pub fees: Amount,
+    pub gas: u64,
+}
+
+/// Horizontal bar chart rendered by `components/chart.html`.
+#[derive(Debug, Clone)]
+pub struct Chart {
+    pub title: String,
+    pub bars: Vec<Bar>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Bar {
+    pub label: String,
+    pub value: String,
+    /// Length relative to the longest bar of the chart.
+    pub percent: u32,
+}
+
+impl Chart {
+    /// Builds a chart from `(label, value, displayed value)` points.
+    pub fn new(title: &str, points: impl IntoIterator<Item = (String, f64, String)>) -> Self {
+        let points: Vec<_> = points.into_iter().collect();
+        let max = points
+            .iter()
+            .map(|(_, value, _)| *value)
+            .fold(0.0, f64::max);
+        Chart {
+            title: title.to_string(),
+            bars: points
+                .into_iter()
+                .map(|(label, value, shown)| Bar {
+                    label,
+                    value: shown,
+                    percent: if max > 0.0 {
+                        (value / max * 100.0).round() as u32
+                    } else {
+                        0
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn units_distribution_fills_gaps() {
+        let distribution = UnitsDistribution::new(EpochId(2), [4, 5, 32, 0]);
+        let buckets: Vec<_> = distribution
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.min, bucket.max, bucket.smeshers))
+            .collect();
+        assert_eq!(
+            buckets,
+            vec![
+                (1, 1, 1),
+                (2, 3, 0),
+                (4, 7, 2),
+                (8, 15, 0),
+                (16, 31, 0),
+                (32, 63, 1)
+            ]
+        );
+        assert!(UnitsDistribution::new(EpochId(2), []).buckets.is_empty());
+    }
+
+    #[test]
+    fn be_u64_handles_any_length() {
+        assert_eq!(be_u64(&[]), 0);
+        assert_eq!(be_u64(&[0x01, 0x02]), 0x0102);
+        assert_eq!(be_u64(&7u64.to_be_bytes()), 7);
+        assert_eq!(be_u64(&[0, 0, 0, 0, 0, 0, 0, 9, 0xff]), 9);
+    }
+
+    #[test]
+    fn transaction_without_blobs_does_not_panic() {
+        let tx = Transaction::from(transactions::Model {
+            id: vec![0xab],
+            tx: None,
+            header: None,
+            result: None,
+            layer: None,
+            block: None,
+            principal: Some(vec![0x01; 3]),
+            nonce: Some(vec![0x05]),
+            timestamp: i64::MAX,
+        });
+
+        assert_eq!(tx.id.to_string(), "ab");
+        assert_eq!(tx.tx, "");
+        assert_eq!(tx.layer, None);
+        assert_eq!(tx.nonce, Some(5));
+        assert_eq!(tx.principal.short(), "010101");
+    }
+
+    #[test]
+    fn pending_age_is_compact() {
+        let tx = transactions::Model {
+            id: vec![0xf0],
+            tx: None,
+            header: None,
+            result: None,
+            layer: None,
+            block: None,
+            principal: None,
+            nonce: None,
+            timestamp: 1_700_000_000_000_000_000,
+        };
+        let received = NaiveDateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+
+        let pending = PendingTx::new(tx.clone(), received + chrono::Duration::seconds(90));
+        assert_eq!(pending.age_label(), "1m");
+        assert_eq!(pending.gas_price, None);
+        assert_eq!(pending.max_fee, None);
+
+        let future = PendingTx::new(tx, received - chrono::Duration::seconds(5));
+        assert_eq!(future.age_label(), "0s");
+    }
+}
@@ -0,0 +1,837 @@
+//! Account templates of the genesis VM and the state they store.
+//!
+//! `accounts.state` holds the SCALE-encoded spawn arguments of the template
+//! the account was spawned from. Decoding never panics: unknown templates or
+//! malformed state yield `None` and callers fall back to the raw hex.
+//!
+//! The other SCALE blobs of the node database, transactions, active sets,
+//! PoET and malfeasance proofs, are decoded here too with the same rules.
+
+use crate::types::{
+    Address, Amount, AtxId, BlockId, EpochId, Hash32, LayerId, NodeId, PoetRef, PublicKey,
+};
+use serde::Serialize;
+use std::fmt;
+
+/// Length of an ed25519 signature.
+const SIGNATURE_LEN: usize = 64;
+/// Address bytes before the part derived from the principal hash.
+const ADDRESS_RESERVED: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Template {
+    Wallet,
+    Multisig,
+    Vesting,
+    Vault,
+}
+
+impl Template {
+    /// Templates are addressed by 23 zero bytes followed by their number.
+    pub fn from_address(address: &[u8]) -> Option<Self> {
+        let (number, zeros) = address.split_last()?;
+        if address.len() != Address::LEN || zeros.iter().any(|byte| *byte != 0) {
+            return None;
+        }
+        match number {
+            1 => Some(Template::Wallet),
+            2 => Some(Template::Multisig),
+            3 => Some(Template::Vesting),
+            4 => Some(Template::Vault),
+            _ => None,
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        let mut address = vec![0; Address::LEN];
+        address[Address::LEN - 1] = *self as u8 + 1;
+        Address(address)
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Template::Wallet => "Wallet",
+            Template::Multisig => "Multisig",
+            Template::Vesting => "Vesting",
+            Template::Vault => "Vault",
+        })
+    }
+}
+
+/// Address of the account spawned from `template` with the encoded `args`.
+pub fn principal(template: Template, args: &[u8]) -> Address {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(template.address().as_bytes());
+    hasher.update(args);
+    let sum = hasher.finalize();
+
+    let mut address = vec![0; ADDRESS_RESERVED];
+    address.extend_from_slice(&sum.as_bytes()[32 - (Address::LEN - ADDRESS_RESERVED)..]);
+    Address(address)
+}
+
+/// Decoded spawn arguments, one variant per template.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "template", rename_all = "lowercase")]
+pub enum AccountState {
+    Wallet(Wallet),
+    Multisig(Multisig),
+    Vesting(Multisig),
+    Vault(Vault),
+}
+
+impl AccountState {
+    pub fn decode(template: Template, state: &[u8]) -> Option<Self> {
+        let mut decoder = Decoder(state);
+        let decoded = Self::read(template, &mut decoder)?;
+        decoder.0.is_empty().then_some(decoded)
+    }
+
+    fn read(template: Template, decoder: &mut Decoder) -> Option<Self> {
+        Some(match template {
+            Template::Wallet => AccountState::Wallet(Wallet::decode(decoder)?),
+            Template::Multisig => AccountState::Multisig(Multisig::decode(decoder)?),
+            Template::Vesting => AccountState::Vesting(Multisig::decode(decoder)?),
+            Template::Vault => AccountState::Vault(Vault::decode(decoder)?),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Wallet {
+    pub public_key: PublicKey,
+}
+
+impl Wallet {
+    fn decode(decoder: &mut Decoder) -> Option<Self> {
+        Some(Wallet {
+            public_key: PublicKey(decoder.fixed(PublicKey::LEN)?.to_vec()),
+        })
+    }
+}
+
+/// A key allowed to sign for a multisig or vesting account, with the wallet
+/// the same key spawns.
+#[derive(Debug, Clone, Serialize)]
+pub struct Signer {
+    pub public_key: PublicKey,
+    pub wallet: Address,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Multisig {
+    pub required: u8,
+    pub signers: Vec<Signer>,
+}
+
+impl Multisig {
+    fn decode(decoder: &mut Decoder) -> Option<Self> {
+        let required = decoder.compact()?.try_into().ok()?;
+        let count = decoder.compact()?;
+
+        let mut signers = vec![];
+        for _ in 0..count {
+            let key = decoder.fixed(PublicKey::LEN)?;
+            signers.push(Signer {
+                public_key: PublicKey(key.to_vec()),
+                wallet: principal(Template::Wallet, key),
+            });
+        }
+
+        Some(Multisig { required, signers })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Vault {
+    pub owner: Address,
+    pub total_amount: Amount,
+    pub initial_unlock_amount: Amount,
+    pub vesting_start: LayerId,
+    pub vesting_end: LayerId,
+}
+
+impl Vault {
+    fn decode(decoder: &mut Decoder) -> Option<Self> {
+        Some(Vault {
+            owner: Address(decoder.fixed(Address::LEN)?.to_vec()),
+            total_amount: Amount(decoder.compact()?),
+            initial_unlock_amount: Amount(decoder.compact()?),
+            vesting_start: LayerId(decoder.compact()?.try_into().ok()?),
+            vesting_end: LayerId(decoder.compact()?.try_into().ok()?),
+        })
+    }
+
+    /// Amount the owner may drain at `layer`: nothing before the start, the
+    /// initial unlock plus a linear share of the rest until the end, then
+    /// everything.
+    pub fn vested(&self, layer: &LayerId) -> Amount {
+        let total = self.total_amount.smidge();
+        let initial = self.initial_unlock_amount.smidge().min(total);
+
+        if *layer < self.vesting_start {
+            return Amount(0);
+        }
+        if *layer >= self.vesting_end {
+            return Amount(total);
+        }
+
+        let elapsed = (layer.0 - self.vesting_start.0) as u128;
+        let duration = (self.vesting_end.0 - self.vesting_start.0) as u128;
+        let linear = (total - initial) as u128 * elapsed / duration;
+        Amount(initial + linear as u64)
+    }
+
+    pub fn locked(&self, layer: &LayerId) -> Amount {
+        Amount(self.total_amount.smidge() - self.vested(layer).smidge())
+    }
+}
+
+const METHOD_SPAWN: u64 = 0;
+const METHOD_SPEND: u64 = 16;
+const METHOD_DRAIN_VAULT: u64 = 17;
+
+/// What a transaction asks its principal's template to do.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Call {
+    /// Spawns the account `template` derives from `args`, either the
+    /// principal itself or, for multisig and vesting principals, another one.
+    Spawn {
+        template: Template,
+        state: AccountState,
+        #[serde(skip)]
+        args: Vec<u8>,
+    },
+    Spend {
+        destination: Address,
+        amount: Amount,
+    },
+    DrainVault {
+        vault: Address,
+        destination: Address,
+        amount: Amount,
+    },
+}
+
+/// The signed part of a raw transaction, without its signatures.
+#[derive(Debug, Clone, Serialize)]
+pub struct RawTx {
+    pub principal: Address,
+    pub nonce: u64,
+    pub gas_price: u64,
+    pub call: Call,
+}
+
+impl RawTx {
+    /// Decodes `transactions.tx`. Unknown versions, methods and templates
+    /// yield `None`.
+    pub fn decode(tx: &[u8]) -> Option<Self> {
+        let mut decoder = Decoder(tx);
+        if decoder.compact()? != 0 {
+            return None;
+        }
+        let principal = Address(decoder.fixed(Address::LEN)?.to_vec());
+        let method = decoder.compact()?;
+        let template = match method {
+            METHOD_SPAWN => Some(Template::from_address(decoder.fixed(Address::LEN)?)?),
+            _ => None,
+        };
+        let nonce = decoder.compact()?;
+        let gas_price = decoder.compact()?;
+
+        let call = match (method, template) {
+            (METHOD_SPAWN, Some(template)) => {
+                let before = decoder.0;
+                let state = AccountState::read(template, &mut decoder)?;
+                Call::Spawn {
+                    template,
+                    state,
+                    args: before[..before.len() - decoder.0.len()].to_vec(),
+                }
+            }
+            (METHOD_SPEND, _) => Call::Spend {
+                destination: Address(decoder.fixed(Address::LEN)?.to_vec()),
+                amount: Amount(decoder.compact()?),
+            },
+            (METHOD_DRAIN_VAULT, _) => Call::DrainVault {
+                vault: Address(decoder.fixed(Address::LEN)?.to_vec()),
+                destination: Address(decoder.fixed(Address::LEN)?.to_vec()),
+                amount: Amount(decoder.compact()?),
+            },
+            _ => return None,
+        };
+
+        Some(RawTx {
+            principal,
+            nonce,
+            gas_price,
+            call,
+        })
+    }
+
+    /// Whether this transaction spawns the account with `template` and
+    /// `state`. Spawn arguments are the account's state and, with the
+    /// template, determine its address.
+    pub fn spawns(&self, template: &[u8], state: &[u8]) -> bool {
+        matches!(
+            &self.call,
+            Call::Spawn { template: spawned, args, .. }
+                if spawned.address().as_bytes() == template && args == state
+        )
+    }
+}
+
+/// `transactions.header`: what the node validated before accepting the
+/// transaction into its mempool.
+#[derive(Debug, Clone, Serialize)]
+pub struct TxHeader {
+    pub principal: Address,
+    pub template: Address,
+    pub method: u8,
+    pub nonce: u64,
+    pub min_layer: u32,
+    pub max_layer: u32,
+    pub max_gas: u64,
+    pub gas_price: u64,
+    pub max_spend: Amount,
+}
+
+impl TxHeader {
+    pub fn decode(header: &[u8]) -> Option<Self> {
+        let mut decoder = Decoder(header);
+        Some(TxHeader {
+            principal: Address(decoder.fixed(Address::LEN)?.to_vec()),
+            template: Address(decoder.fixed(Address::LEN)?.to_vec()),
+            method: decoder.compact()?.try_into().ok()?,
+            nonce: decoder.compact()?,
+            min_layer: decoder.compact()?.try_into().ok()?,
+            max_layer: decoder.compact()?.try_into().ok()?,
+            max_gas: decoder.compact()?,
+            gas_price: decoder.compact()?,
+            max_spend: Amount(decoder.compact()?),
+        })
+    }
+
+    /// The most the principal can be charged for gas.
+    pub fn max_fee(&self) -> Amount {
+        Amount(self.max_gas.saturating_mul(self.gas_price))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TxStatus {
+    Success,
+    Failure,
+    Invalid,
+}
+
+impl fmt::Display for TxStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TxStatus::Success => "Success",
+            TxStatus::Failure => "Failure",
+            TxStatus::Invalid => "Invalid",
+        })
+    }
+}
+
+/// `transactions.result`: how executing the transaction went. Failed
+/// transactions still pay their fee, invalid ones are dropped.
+#[derive(Debug, Clone, Serialize)]
+pub struct TxResult {
+    pub status: TxStatus,
+    pub message: String,
+    pub gas: u64,
+    pub fee: Amount,
+    pub block: BlockId,
+    pub layer: LayerId,
+    pub addresses: Vec<Address>,
+}
+
+impl TxResult {
+    pub fn decode(result: &[u8]) -> Option<Self> {
+        let mut decoder = Decoder(result);
+        let status = match decoder.compact()? {
+            0 => TxStatus::Success,
+            1 => TxStatus::Failure,
+            2 => TxStatus::Invalid,
+            _ => return None,
+        };
+        let message_len = decoder.compact()?.try_into().ok()?;
+        let message = String::from_utf8_lossy(decoder.fixed(message_len)?).into_owned();
+        let gas = decoder.compact()?;
+        let fee = Amount(decoder.compact()?);
+        let block = BlockId(decoder.fixed(BlockId::LEN)?.to_vec());
+        let layer = LayerId(decoder.compact()?.try_into().ok()?);
+
+        let count = decoder.compact()?;
+        let mut addresses = vec![];
+        for _ in 0..count {
+            addresses.push(Address(decoder.fixed(Address::LEN)?.to_vec()));
+        }
+
+        Some(TxResult {
+            status,
+            message,
+            gas,
+            fee,
+            block,
+            layer,
+            addresses,
+        })
+    }
+}
+
+/// `activesets.active_set`: the ATXs whose smeshers are eligible to publish
+/// ballots in an epoch.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveSet {
+    pub epoch: EpochId,
+    pub atxs: Vec<AtxId>,
+}
+
+impl ActiveSet {
+    pub fn decode(active_set: &[u8]) -> Option<Self> {
+        let mut decoder = Decoder(active_set);
+        let epoch = EpochId(decoder.compact()?.try_into().ok()?);
+
+        let count = decoder.compact()?;
+        let mut atxs = vec![];
+        for _ in 0..count {
+            atxs.push(AtxId(decoder.fixed(AtxId::LEN)?.to_vec()));
+        }
+
+        Some(ActiveSet { epoch, atxs })
+    }
+}
+
+/// `poets.poet`: the proof a PoET service published at the end of a round.
+#[derive(Debug, Clone, Serialize)]
+pub struct PoetProof {
+    pub root: Hash32,
+    pub proven_leaves: usize,
+    pub proof_nodes: usize,
+    pub leaf_count: u64,
+    pub service_id: String,
+    pub round_id: String,
+}
+
+impl PoetProof {
+    pub fn decode(poet: &[u8]) -> Option<Self> {
+        let mut decoder = Decoder(poet);
+        let root = Hash32(decoder.fixed(Hash32::LEN)?.to_vec());
+        let proven_leaves = decoder.skip_byte_slices()?;
+        let proof_nodes = decoder.skip_byte_slices()?;
+        let leaf_count = decoder.compact()?;
+        let service_id = hex::encode(decoder.byte_slice()?);
+        let round_id = String::from_utf8_lossy(decoder.byte_slice()?).into_owned();
+
+        Some(PoetProof {
+            root,
+            proven_leaves,
+            proof_nodes,
+            leaf_count,
+            service_id,
+            round_id,
+        })
+    }
+}
+
+/// The PoET proof a version 1 `atxs.atx` references: the challenge of its
+/// NIPost metadata. `None` for golden ATXs without a NIPost.
+pub fn atx_poet_ref(atx: &[u8]) -> Option<PoetRef> {
+    let mut decoder = Decoder(atx);
+
+    // NIPost challenge: publish epoch, sequence, previous and positioning
+    // ATX, then the optional commitment ATX and initial post.
+    decoder.compact()?;
+    decoder.compact()?;
+    decoder.fixed(AtxId::LEN * 2)?;
+    if decoder.option()? {
+        decoder.fixed(AtxId::LEN)?;
+    }
+    if decoder.option()? {
+        decoder.post()?;
+    }
+    decoder.fixed(Address::LEN)?;
+    decoder.compact()?;
+
+    if !decoder.option()? {
+        return None;
+    }
+    // Membership proof of the challenge in the PoET tree.
+    let nodes = decoder.compact()?.try_into().ok()?;
+    decoder.fixed(Hash32::LEN.checked_mul(nodes)?)?;
+    decoder.compact()?;
+    if decoder.option()? {
+        decoder.post()?;
+    }
+    if !decoder.option()? {
+        return None;
+    }
+    let challenge = decoder.byte_slice()?;
+    (challenge.len() == PoetRef::LEN).then(|| PoetRef(challenge.to_vec()))
+}
+
+/// What a malicious smesher was caught doing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Offense {
+    /// Two ATXs published for the same epoch.
+    MultipleAtxs {
+        epoch: EpochId,
+    },
+    /// Two ballots cast in the same layer.
+    DoubleBallot {
+        layer: LayerId,
+    },
+    /// Two different hare messages sent in the same round.
+    HareEquivocation {
+        layer: LayerId,
+        round: u32,
+    },
+    InvalidPostIndex,
+    InvalidPrevAtx,
+}
+
+impl Offense {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Offense::MultipleAtxs { .. } => "Multiple ATXs",
+            Offense::DoubleBallot { .. } => "Double ballot",
+            Offense::HareEquivocation { .. } => "Hare equivocation",
+            Offense::InvalidPostIndex => "Invalid PoST index",
+            Offense::InvalidPrevAtx => "Invalid previous ATX",
+        }
+    }
+}
+
+/// `identities.proof`: evidence that a smesher equivocated, gossiped in the
+/// layer it was detected.
+#[derive(Debug, Clone, Serialize)]
+pub struct MalfeasanceProof {
+    pub layer: LayerId,
+    pub offense: Offense,
+    /// Signer of the conflicting messages, when the proof carries them.
+    pub smesher: Option<NodeId>,
+}
+
+impl MalfeasanceProof {
+    pub fn decode(proof: &[u8]) -> Option<Self> {
+        let mut decoder = Decoder(proof);
+        let layer = LayerId(decoder.compact()?.try_into().ok()?);
+        let kind = decoder.fixed(1)?[0];
+
+        // The conflicting messages share their metadata, so the offense is
+        // read from the first and the second only has to be present.
+        let mut read_messages = |metadata: fn(&mut Decoder) -> Option<Offense>| {
+            let offense = metadata(&mut decoder)?;
+            decoder.fixed(Hash32::LEN)?;
+            let smesher = NodeId(decoder.fixed(NodeId::LEN)?.to_vec());
+            decoder.fixed(SIGNATURE_LEN)?;
+            metadata(&mut decoder)?;
+            decoder.fixed(Hash32::LEN + NodeId::LEN + SIGNATURE_LEN)?;
+            Some((offense, Some(smesher)))
+        };
+        let (offense, smesher) = match kind {
+            1 => read_messages(|decoder| {
+                Some(Offense::MultipleAtxs {
+                    epoch: EpochId(decoder.compact()?.try_into().ok()?),
+                })
+            })?,
+            2 => read_messages(|decoder| {
+                Some(Offense::DoubleBallot {
+                    layer: LayerId(decoder.compact()?.try_into().ok()?),
+                })
+            })?,
+            3 => read_messages(|decoder| {
+                Some(Offense::HareEquivocation {
+                    layer: LayerId(decoder.compact()?.try_into().ok()?),
+                    round: decoder.compact()?.try_into().ok()?,
+                })
+            })?,
+            4 => (Offense::InvalidPostIndex, None),
+            5 => (Offense::InvalidPrevAtx, None),
+            _ => return None,
+        };
+
+        Some(MalfeasanceProof {
+            layer,
+            offense,
+            smesher,
+        })
+    }
+}
+
+/// Reads the subset of SCALE the templates use.
+struct Decoder<'a>(&'a [u8]);
+
+impl<'a> Decoder<'a> {
+    fn fixed(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(bytes)
+    }
+
+    /// Compact integer: the two low bits of the first byte select a 1, 2 or
+    /// 4 byte little-endian value shifted by two, or a length-prefixed one.
+    fn compact(&mut self) -> Option<u64> {
+        let mode = *self.0.first()? & 0b11;
+        let value = match mode {
+            0b00 => (self.fixed(1)?[0] >> 2) as u64,
+            0b01 => (u16::from_le_bytes(self.fixed(2)?.try_into().ok()?) >> 2) as u64,
+            0b10 => (u32::from_le_bytes(self.fixed(4)?.try_into().ok()?) >> 2) as u64,
+            _ => {
+                let len = (self.fixed(1)?[0] >> 2) as usize + 4;
+                if len > 8 {
+                    return None;
+                }
+                let mut buffer = [0u8; 8];
+                buffer[..len].copy_from_slice(self.fixed(len)?);
+                u64::from_le_bytes(buffer)
+            }
+        };
+        Some(value)
+    }
+
+    /// Optional values are prefixed with a 0 or 1 byte.
+    fn option(&mut self) -> Option<bool> {
+        match self.fixed(1)?[0] {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+
+    /// Length-prefixed bytes.
+    fn byte_slice(&mut self) -> Option<&'a [u8]> {
+        let len = self.compact()?.try_into().ok()?;
+        self.fixed(len)
+    }
+
+    /// Skips a list of length-prefixed bytes, returning its length.
+    fn skip_byte_slices(&mut self) -> Option<usize> {
+        let count = self.compact()?.try_into().ok()?;
+        for _ in 0..count {
+            self.byte_slice()?;
+        }
+        Some(count)
+    }
+
+    /// Skips a PoST proof: nonce, label indices and proof of work.
+    fn post(&mut self) -> Option<()> {
+        self.compact()?;
+        self.byte_slice()?;
+        self.compact()?;
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{
+        active_set, atx_v1, compact, malfeasance_proof, multisig_state, poet_proof, spawn_tx,
+        spend_tx, tx_header, tx_result, vault_state,
+    };
+
+    #[test]
+    fn compact_round_trips() {
+        for value in [
+            0,
+            1,
+            63,
+            64,
+            16_383,
+            16_384,
+            1 << 30,
+            u32::MAX as u64,
+            u64::MAX,
+        ] {
+            assert_eq!(Decoder(&compact(value)).compact(), Some(value));
+        }
+        assert_eq!(Decoder(&[0b01]).compact(), None);
+    }
+
+    #[test]
+    fn templates_round_trip_through_addresses() {
+        for template in [
+            Template::Wallet,
+            Template::Multisig,
+            Template::Vesting,
+            Template::Vault,
+        ] {
+            assert_eq!(
+                Template::from_address(template.address().as_bytes()),
+                Some(template)
+            );
+        }
+        assert_eq!(Template::from_address(&[0; 24]), None);
+        assert_eq!(Template::from_address(&[1]), None);
+    }
+
+    #[test]
+    fn decodes_multisig_signers() {
+        let state = multisig_state(2, &[[0x21; 32], [0x22; 32], [0x23; 32]]);
+
+        let Some(AccountState::Multisig(multisig)) =
+            AccountState::decode(Template::Multisig, &state)
+        else {
+            panic!("not a multisig");
+        };
+        assert_eq!(multisig.required, 2);
+        assert_eq!(multisig.signers.len(), 3);
+        assert_eq!(multisig.signers[1].public_key, PublicKey(vec![0x22; 32]));
+        assert_eq!(
+            multisig.signers[1].wallet,
+            principal(Template::Wallet, &[0x22; 32])
+        );
+        assert_eq!(multisig.signers[1].wallet.as_bytes()[..4], [0; 4]);
+
+        assert!(AccountState::decode(Template::Multisig, &state[..state.len() - 1]).is_none());
+        assert!(AccountState::decode(Template::Wallet, &state).is_none());
+    }
+
+    #[test]
+    fn vault_vests_linearly() {
+        let state = vault_state(&[0xaa; 24], 1_000, 100, 10, 20);
+        let Some(AccountState::Vault(vault)) = AccountState::decode(Template::Vault, &state) else {
+            panic!("not a vault");
+        };
+
+        assert_eq!(vault.owner, Address(vec![0xaa; 24]));
+        assert_eq!(vault.vested(&LayerId(9)), Amount(0));
+        assert_eq!(vault.vested(&LayerId(10)), Amount(100));
+        assert_eq!(vault.vested(&LayerId(15)), Amount(550));
+        assert_eq!(vault.locked(&LayerId(15)), Amount(450));
+        assert_eq!(vault.vested(&LayerId(20)), Amount(1_000));
+        assert_eq!(vault.locked(&LayerId(30)), Amount(0));
+    }
+
+    #[test]
+    fn decodes_spawn_and_spend_transactions() {
+        let state = multisig_state(1, &[[0x21; 32]]);
+        let spawn = RawTx::decode(&spawn_tx(&[0xaa; 24], Template::Multisig, 0, &state)).unwrap();
+        assert_eq!(spawn.principal, Address(vec![0xaa; 24]));
+        assert_eq!(spawn.nonce, 0);
+        assert!(spawn.spawns(Template::Multisig.address().as_bytes(), &state));
+        assert!(!spawn.spawns(Template::Vesting.address().as_bytes(), &state));
+        assert!(!spawn.spawns(Template::Multisig.address().as_bytes(), &state[1..]));
+
+        let spend = RawTx::decode(&spend_tx(&[0xaa; 24], 7, &[0xbb; 24], 5_000)).unwrap();
+        assert_eq!(spend.nonce, 7);
+        assert!(matches!(
+            spend.call,
+            Call::Spend {
+                amount: Amount(5_000),
+                ..
+            }
+        ));
+        assert!(!spend.spawns(Template::Wallet.address().as_bytes(), &[]));
+
+        assert!(RawTx::decode(&[0x01, 0x02]).is_none());
+    }
+
+    #[test]
+    fn decodes_headers() {
+        let header =
+            TxHeader::decode(&tx_header(&[0xaa; 24], Template::Wallet, 9, 5_000, 2)).unwrap();
+        assert_eq!(header.principal, Address(vec![0xaa; 24]));
+        assert_eq!(header.template, Template::Wallet.address());
+        assert_eq!(header.nonce, 9);
+        assert_eq!(header.max_fee(), Amount(10_000));
+
+        assert!(TxHeader::decode(&[0x02, 0x00]).is_none());
+    }
+
+    #[test]
+    fn decodes_results() {
+        let encoded = tx_result(
+            TxStatus::Failure,
+            "no funds",
+            3,
+            &[0xbb; 20],
+            7,
+            &[&[0xaa; 24]],
+        );
+        let result = TxResult::decode(&encoded).unwrap();
+        assert_eq!(result.status, TxStatus::Failure);
+        assert_eq!(result.message, "no funds");
+        assert_eq!(result.fee, Amount(3));
+        assert_eq!(result.block, BlockId(vec![0xbb; 20]));
+        assert_eq!(result.layer, LayerId(7));
+        assert_eq!(result.addresses, vec![Address(vec![0xaa; 24])]);
+
+        assert!(TxResult::decode(&encoded[..encoded.len() - 1]).is_none());
+        assert!(TxResult::decode(&[0x0c]).is_none());
+    }
+
+    #[test]
+    fn decodes_active_sets() {
+        let encoded = active_set(5, &[&[0xe1; 32], &[0xe2; 32]]);
+        let set = ActiveSet::decode(&encoded).unwrap();
+        assert_eq!(set.epoch, EpochId(5));
+        assert_eq!(set.atxs, vec![AtxId(vec![0xe1; 32]), AtxId(vec![0xe2; 32])]);
+
+        assert!(ActiveSet::decode(&encoded[..encoded.len() - 1]).is_none());
+    }
+
+    #[test]
+    fn decodes_poet_proofs() {
+        let encoded = poet_proof(&[0x5e; 4], "12", 3, 1 << 20);
+        let proof = PoetProof::decode(&encoded).unwrap();
+        assert_eq!(proof.proven_leaves, 3);
+        assert_eq!(proof.proof_nodes, 1);
+        assert_eq!(proof.leaf_count, 1 << 20);
+        assert_eq!(proof.service_id, "5e5e5e5e");
+        assert_eq!(proof.round_id, "12");
+
+        assert!(PoetProof::decode(&encoded[..40]).is_none());
+    }
+
+    #[test]
+    fn finds_the_poet_proof_of_atxs() {
+        let atx = atx_v1(4, &[0xaa; 24], &[0xa2; 32]);
+        assert_eq!(atx_poet_ref(&atx), Some(PoetRef(vec![0xa2; 32])));
+
+        assert!(atx_poet_ref(&atx_v1(4, &[0xaa; 24], &[0xa2; 8])).is_none());
+        assert!(atx_poet_ref(&atx[..100]).is_none());
+    }
+
+    #[test]
+    fn decodes_malfeasance_proofs() {
+        let smesher = [0x72; 32];
+        let hare = malfeasance_proof(6, 3, &smesher, &[compact(5), compact(2)].concat());
+        let proof = MalfeasanceProof::decode(&hare).unwrap();
+        assert_eq!(proof.layer, LayerId(6));
+        assert_eq!(
+            proof.offense,
+            Offense::HareEquivocation {
+                layer: LayerId(5),
+                round: 2
+            }
+        );
+        assert_eq!(proof.smesher, Some(NodeId(smesher.to_vec())));
+
+        let ballot = malfeasance_proof(9, 2, &smesher, &compact(8));
+        assert_eq!(
+            MalfeasanceProof::decode(&ballot).unwrap().offense,
+            Offense::DoubleBallot { layer: LayerId(8) }
+        );
+        let post = MalfeasanceProof::decode(&malfeasance_proof(9, 4, &[], &[])).unwrap();
+        assert_eq!(post.offense, Offense::InvalidPostIndex);
+        assert_eq!(post.smesher, None);
+
+        // The second message is required.
+        assert!(MalfeasanceProof::decode(&hare[..hare.len() - 1]).is_none());
+        assert!(MalfeasanceProof::decode(&[0, 9]).is_none());
+    }
+}